@@ -1,86 +1,80 @@
 #!/bin/bash
 set -e
-echo "Running $1, $2, $3, $4, $5"
 
-# Get program directory name as $1 and append "-$2" to it if $1 == "tendermint"
-if [ "$1" = "tendermint" ] || [ "$1" = "reth" ]; then
-    program_directory="${1}-$2"
-else
-    program_directory="$1"
-fi
+# Usage:
+#   ./eval.sh <program> <prover> <hashfn> <shard_size> <filename> [block_number | fixture]
+#   ./eval.sh sweep [config] [--fresh]
+#
+# The eval binary builds the guest programs before running them, skipping the ones whose sources
+# haven't changed since their last build.
 
-echo "Building program"
+# Run the eval binary with the given arguments, with the features $1 enabled next to the GPU ones.
+run_eval() {
+    echo "Running eval script"
 
-# cd to program directory computed above
-cd "programs/$program_directory"
+    # Detect whether we're on an instance with a GPU.
+    if nvidia-smi > /dev/null 2>&1; then
+      GPU_EXISTS=true
+    else
+      GPU_EXISTS=false
+    fi
 
-# If the prover is sp1, then build the program.
-if [ "$2" == "sp1" ]; then
-    # The reason we don't just use `cargo prove build` from the SP1 CLI is we need to pass a --features ...
-    # flag to select between sp1 and risc0.
-    RUSTFLAGS="-C passes=loweratomic -C link-arg=-Ttext=0x00200800 -C panic=abort" \
-        RUSTUP_TOOLCHAIN=succinct \
-        CARGO_BUILD_TARGET=riscv32im-succinct-zkvm-elf \
-        cargo build --release --ignore-rust-version --features $2
-fi
-# If the prover is risc0, then build the program.
-if [ "$2" == "risc0" ]; then
-    echo "Building Risc0"
-    # Use the risc0 toolchain.
-    RUSTFLAGS="-C passes=loweratomic -C link-arg=-Ttext=0x00200800 -C panic=abort" \
-        RUSTUP_TOOLCHAIN=risc0 \
-        CARGO_BUILD_TARGET=riscv32im-risc0-zkvm-elf \
-        cargo build --release --ignore-rust-version --features $2
-fi
+    # Check for AVX-512 support
+    if lscpu | grep -q avx512; then
+      # If AVX-512 is supported, add the specific features to RUSTFLAGS
+      export RUSTFLAGS="-C target-cpu=native -C target-feature=+avx512ifma,+avx512vl"
+    else
+      # If AVX-512 is not supported, just set target-cpu=native
+      export RUSTFLAGS="-C target-cpu=native"
+    fi
 
-cd ../../
+    # Set the logging level.
+    export RUST_LOG=info
 
-echo "Running eval script"
+    # Determine the features based on GPU existence.
+    FEATURES="$1"
+    if [ "$GPU_EXISTS" = true ]; then
+      FEATURES="${FEATURES:+$FEATURES, }cuda"
+    fi
+    shift
 
-# Detect whether we're on an instance with a GPU.
-if nvidia-smi > /dev/null 2>&1; then
-  GPU_EXISTS=true
-else
-  GPU_EXISTS=false
-fi
+    # Run the benchmark.
+    cargo run \
+        -p sp1-benchmarks-eval \
+        --release \
+        --no-default-features \
+        --features "${FEATURES:-default}" \
+        -- \
+        "$@"
+}
 
-# Check for AVX-512 support
-if lscpu | grep -q avx512; then
-  # If AVX-512 is supported, add the specific features to RUSTFLAGS
-  export RUSTFLAGS="-C target-cpu=native -C target-feature=+avx512ifma,+avx512vl"
-else
-  # If AVX-512 is not supported, just set target-cpu=native
-  export RUSTFLAGS="-C target-cpu=native"
+if [ "$1" = "sweep" ]; then
+    # A sweep runs every prover in one process, so every backend is compiled in.
+    run_eval "risc0, fuel" sweep --config "${2:-sweep.toml}" "${@:3}"
+    exit 0
 fi
 
-# Set the logging level.
-export RUST_LOG=info
+echo "Running $1, $2, $3, $4, $5"
 
-# Determine the features based on GPU existence.
-if [ "$GPU_EXISTS" = true ]; then
-  FEATURES="cuda"
+if [ "$2" == "risc0" ]; then
+    BACKEND_FEATURES="risc0"
 else
-  FEATURES="default"
+    BACKEND_FEATURES=""
 fi
 
-if [ "$2" == "risc0" ]; then
- if [ "$GPU_EXISTS" = true ]; then 
-  FEATURES="risc0, cuda"
- else
- FEATURES="risc0" 
- fi
+# The Fuel programs read a fixture instead of a block.
+if [[ "$1" == fuel-* ]]; then
+    BACKEND_FEATURES="${BACKEND_FEATURES:+$BACKEND_FEATURES, }fuel"
+    INPUT_ARGS="${6:+--fixture $6}"
+else
+    INPUT_ARGS="${6:+--block-number $6}"
 fi
 
-# Run the benchmark.
-cargo run \
-    -p sp1-benchmarks-eval \
-    --release \
-    --no-default-features \
-    --features "$FEATURES" \
-    -- \
+run_eval "$BACKEND_FEATURES" \
+    run \
     --program "$1" \
     --prover "$2" \
     --hashfn "$3" \
     --shard-size "$4" \
     --filename "$5" \
-    ${6:+--block-number $6}
+    $INPUT_ARGS
@@ -1,7 +1,44 @@
+use std::{env, fs, path::PathBuf};
+
+/// The crates whose resolved versions are recorded in every report.
+const TRACKED_CRATES: [(&str, &str); 2] =
+    [("sp1-prover", "EVAL_SP1_VERSION"), ("risc0-zkvm", "EVAL_RISC0_VERSION")];
+
 fn main() {
-    vergen::EmitBuilder::builder()
-        .build_timestamp()
-        .git_sha(true)
-        .emit()
-        .unwrap();
-}
\ No newline at end of file
+    vergen::EmitBuilder::builder().build_timestamp().git_sha(true).rustc_semver().emit().unwrap();
+
+    emit_crate_versions();
+}
+
+/// Emits the versions of the tracked crates resolved in the workspace lockfile.
+///
+/// Git dependencies are reported with the commit they are pinned to, e.g. `3.0.0 (ca71f2d)`.
+fn emit_crate_versions() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let lockfile = manifest_dir.join("..").join("Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lockfile.display());
+
+    let packages = fs::read_to_string(&lockfile)
+        .ok()
+        .and_then(|contents| contents.parse::<toml::Table>().ok())
+        .and_then(|lock| lock.get("package").and_then(|p| p.as_array()).cloned())
+        .unwrap_or_default();
+
+    for (name, var) in TRACKED_CRATES {
+        let package =
+            packages.iter().find(|p| p.get("name").and_then(|n| n.as_str()) == Some(name));
+        let version = package
+            .and_then(|p| p.get("version").and_then(|v| v.as_str()))
+            .map(|version| {
+                let source = package.and_then(|p| p.get("source")).and_then(|s| s.as_str());
+                match source.and_then(|s| s.strip_prefix("git+")).and_then(|s| s.split_once('#')) {
+                    Some((_, commit)) => {
+                        format!("{} ({})", version, &commit[..commit.len().min(7)])
+                    }
+                    None => version.to_string(),
+                }
+            })
+            .unwrap_or_else(|| "unknown".to_string());
+        println!("cargo:rustc-env={}={}", var, version);
+    }
+}
@@ -0,0 +1,284 @@
+use std::{
+    fs,
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::{Path, PathBuf},
+};
+
+use revm::InMemoryDB;
+use serde::{Deserialize, Serialize};
+use sp1_reth_primitives::{db::InMemoryDBHelper, processor::EvmProcessor, SP1RethInput};
+
+use crate::error::{EvalError, Result};
+
+/// The directory of the block inputs, relative to the root of the repository.
+pub const BLOCKS_DIR: &str = "eval/blocks";
+
+/// The name of the manifest in the directory of the block inputs.
+const MANIFEST_NAME: &str = "manifest.toml";
+
+/// The gas bands blocks are grouped into, by name and inclusive lower bound of gas used.
+const GAS_BANDS: [(&str, u64); 3] = [("small", 0), ("medium", 10_000_000), ("large", 20_000_000)];
+
+/// The arguments of the blocks command.
+#[derive(clap::Args, Clone)]
+pub struct BlocksArgs {
+    /// The directory of the block inputs to index.
+    #[arg(long, default_value = BLOCKS_DIR)]
+    dir: PathBuf,
+}
+
+/// What is known about a block input, as stored in the manifest.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BlockEntry {
+    /// The number of the block.
+    pub number: u64,
+    /// A label to select the block by in sweeps, kept when the directory is indexed again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// The number of transactions in the block.
+    pub transactions: usize,
+    /// The gas used by executing the block.
+    pub gas_used: u64,
+    /// The gas band of the block, which it can also be selected by in sweeps.
+    pub gas_band: String,
+    /// The number of accounts in the parent state of the input.
+    pub accounts: usize,
+    /// The number of storage slots in the parent state of the input.
+    pub storage_slots: usize,
+    /// The size of the input file in bytes.
+    pub size: u64,
+}
+
+/// The index of the block inputs, written by the blocks command.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// The indexed blocks, by number.
+    #[serde(default)]
+    pub blocks: Vec<BlockEntry>,
+}
+
+/// A block of a sweep, selected by number, label or gas band.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum BlockSelector {
+    /// The block with the number.
+    Number(u64),
+    /// The blocks with the label or in the gas band.
+    Name(String),
+}
+
+/// Returns the gas band of a block that used the gas.
+fn gas_band(gas_used: u64) -> &'static str {
+    GAS_BANDS.iter().rev().find(|(_, min)| gas_used >= *min).map(|(name, _)| *name).unwrap()
+}
+
+impl Manifest {
+    /// The path of the manifest of the directory.
+    pub fn path(dir: &Path) -> PathBuf {
+        dir.join(MANIFEST_NAME)
+    }
+
+    /// Reads the manifest of the directory.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = Self::path(dir);
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            EvalError::Config(format!(
+                "failed to read block manifest {}: {}, run the blocks command to create it",
+                path.display(),
+                e
+            ))
+        })?;
+        toml::from_str(&contents).map_err(|e| {
+            EvalError::Config(format!("invalid block manifest {}: {}", path.display(), e))
+        })
+    }
+
+    /// Returns the numbers of the selected blocks, in the order they are selected.
+    ///
+    /// A name selects every block with that label or in that gas band, in order of number.
+    pub fn select(&self, selectors: &[BlockSelector]) -> Result<Vec<u64>> {
+        let mut numbers = Vec::new();
+        for selector in selectors {
+            match selector {
+                BlockSelector::Number(number) => numbers.push(*number),
+                BlockSelector::Name(name) => {
+                    let selected: Vec<u64> = self
+                        .blocks
+                        .iter()
+                        .filter(|b| b.label.as_deref() == Some(name) || &b.gas_band == name)
+                        .map(|b| b.number)
+                        .collect();
+                    if selected.is_empty() {
+                        return Err(EvalError::Config(format!(
+                            "no block is labeled {} or in the {} gas band",
+                            name, name
+                        )));
+                    }
+                    numbers.extend(selected);
+                }
+            }
+        }
+        Ok(numbers)
+    }
+}
+
+/// Returns the gas used by the block, if the block directory is indexed and has an entry for it.
+pub fn gas_used(number: u64) -> Option<u64> {
+    let manifest = Manifest::load(Path::new(BLOCKS_DIR)).ok()?;
+    manifest.blocks.iter().find(|block| block.number == number).map(|block| block.gas_used)
+}
+
+/// Selects blocks by number only, or through the manifest of the block directory if any block is
+/// selected by name.
+pub fn select_blocks(selectors: &[BlockSelector]) -> Result<Vec<u64>> {
+    if selectors.iter().all(|s| matches!(s, BlockSelector::Number(_))) {
+        return Manifest::default().select(selectors);
+    }
+    Manifest::load(Path::new(BLOCKS_DIR))?.select(selectors)
+}
+
+/// Deserializes and executes the block input at the path, and describes it.
+fn index_block(path: &Path, number: u64) -> std::result::Result<BlockEntry, String> {
+    let bytes = fs::read(path).map_err(|e| format!("failed to read: {}", e))?;
+    let mut input: SP1RethInput =
+        bincode::deserialize(&bytes).map_err(|e| format!("failed to deserialize: {}", e))?;
+
+    let transactions = input.transactions.len();
+    let accounts = input.parent_storage.len();
+    let storage_slots = input.parent_storage.values().map(|(_, slots)| slots.len()).sum();
+
+    // Execute the block the way the reth guest does, to learn how much gas it uses.
+    let gas_used = catch_unwind(AssertUnwindSafe(|| {
+        let db = InMemoryDB::initialize(&mut input).unwrap();
+        let mut executor = EvmProcessor::<InMemoryDB> { input, db: Some(db), header: None };
+        executor.initialize();
+        executor.execute();
+        executor.finalize();
+        executor.header.unwrap().gas_used
+    }))
+    .map_err(|_| "failed to execute".to_string())?;
+
+    Ok(BlockEntry {
+        number,
+        label: None,
+        transactions,
+        gas_used,
+        gas_band: gas_band(gas_used).to_string(),
+        accounts,
+        storage_slots,
+        size: bytes.len() as u64,
+    })
+}
+
+/// Indexes the block inputs of the directory and writes its manifest.
+///
+/// The labels of the previous manifest are kept. Blocks that fail to deserialize or execute are
+/// left out of the manifest and reported as a configuration error once every block is indexed.
+pub fn run(args: &BlocksArgs) -> Result<()> {
+    let previous = Manifest::load(&args.dir).unwrap_or_default();
+    let read_error = |e: std::io::Error| {
+        EvalError::Config(format!("failed to read blocks directory {}: {}", args.dir.display(), e))
+    };
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(&args.dir).map_err(read_error)? {
+        let path = entry.map_err(read_error)?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("bin") {
+            continue;
+        }
+        match path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse::<u64>().ok()) {
+            Some(number) => files.push((number, path)),
+            None => println!("Skipping {}: not named after a block number", path.display()),
+        }
+    }
+    files.sort();
+
+    println!(
+        "{:>10} {:>12} {:>12} {:>8} {:>10} {:>10} {:>10}  label",
+        "block", "transactions", "gas_used", "band", "accounts", "slots", "size"
+    );
+    let mut manifest = Manifest::default();
+    let mut failed = Vec::new();
+    for (number, path) in files {
+        match index_block(&path, number) {
+            Ok(mut entry) => {
+                entry.label = previous
+                    .blocks
+                    .iter()
+                    .find(|b| b.number == number)
+                    .and_then(|b| b.label.clone());
+                println!(
+                    "{:>10} {:>12} {:>12} {:>8} {:>10} {:>10} {:>10}  {}",
+                    entry.number,
+                    entry.transactions,
+                    entry.gas_used,
+                    entry.gas_band,
+                    entry.accounts,
+                    entry.storage_slots,
+                    entry.size,
+                    entry.label.as_deref().unwrap_or("")
+                );
+                manifest.blocks.push(entry);
+            }
+            Err(err) => {
+                println!("{:>10} {}", number, err);
+                failed.push(number.to_string());
+            }
+        }
+    }
+
+    let path = Manifest::path(&args.dir);
+    fs::write(&path, toml::to_string(&manifest).unwrap()).map_err(|e| {
+        EvalError::Config(format!("failed to write block manifest {}: {}", path.display(), e))
+    })?;
+    println!("Wrote {} blocks to {}", manifest.blocks.len(), path.display());
+
+    if !failed.is_empty() {
+        return Err(EvalError::Config(format!("invalid block inputs: {}", failed.join(", "))));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(number: u64, label: Option<&str>, gas_used: u64) -> BlockEntry {
+        BlockEntry {
+            number,
+            label: label.map(String::from),
+            transactions: 0,
+            gas_used,
+            gas_band: gas_band(gas_used).to_string(),
+            accounts: 0,
+            storage_slots: 0,
+            size: 0,
+        }
+    }
+
+    #[test]
+    fn blocks_are_selected_by_number_label_or_gas_band() {
+        let manifest = Manifest {
+            blocks: vec![
+                entry(1, Some("transfers"), 5_000_000),
+                entry(2, None, 12_000_000),
+                entry(3, None, 29_000_000),
+                entry(4, Some("transfers"), 21_000_000),
+            ],
+        };
+        let select = |selectors: &str| {
+            let config: toml::Table = toml::from_str(&format!("blocks = {}", selectors)).unwrap();
+            let selectors: Vec<BlockSelector> = config["blocks"].clone().try_into().unwrap();
+            manifest.select(&selectors)
+        };
+
+        assert_eq!(select("[7, \"transfers\"]").unwrap(), [7, 1, 4]);
+        assert_eq!(select("[\"large\", \"small\"]").unwrap(), [3, 4, 1]);
+        assert!(select("[\"unknown\"]").is_err());
+
+        let written = toml::to_string(&manifest).unwrap();
+        assert_eq!(toml::from_str::<Manifest>(&written).unwrap().blocks, manifest.blocks);
+    }
+}
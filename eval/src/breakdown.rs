@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The syscalls that are handled by the executor rather than proven by a precompile chip.
+const NON_PRECOMPILE_SYSCALLS: [&str; 9] = [
+    "HALT",
+    "WRITE",
+    "ENTER_UNCONSTRAINED",
+    "EXIT_UNCONSTRAINED",
+    "COMMIT",
+    "COMMIT_DEFERRED_PROOFS",
+    "VERIFY_SP1_PROOF",
+    "HINT_LEN",
+    "HINT_READ",
+];
+
+/// What an execution spent its cycles on, as far as the zkVM reports it.
+///
+/// SP1 reports the instructions, syscalls and touched memory of an execution, while RISC0 reports
+/// how its cycles split between the guest, paging and the reserved cycles of each segment. What a
+/// zkVM doesn't report is left empty.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ExecutionBreakdown {
+    /// The number of instructions executed by class, e.g. `alu`, `load` or `branch`.
+    pub instruction_classes: BTreeMap<String, u64>,
+    /// The number of instructions executed by opcode.
+    pub opcodes: BTreeMap<String, u64>,
+    /// The number of invocations by syscall.
+    pub syscalls: BTreeMap<String, u64>,
+    /// The number of syscalls proven by a precompile chip.
+    pub precompile_invocations: Option<u64>,
+    /// The number of distinct memory addresses the execution touched.
+    pub touched_memory_addresses: Option<u64>,
+    /// The cycles counted by the cycle tracker of the guest, by span.
+    pub cycle_tracker: BTreeMap<String, u64>,
+    /// The cycles of the guest's own instructions.
+    pub user_cycles: Option<u64>,
+    /// The cycles spent paging memory in and out of the segments.
+    pub paging_cycles: Option<u64>,
+    /// The cycles reserved by every segment for its own bookkeeping.
+    pub reserved_cycles: Option<u64>,
+    /// The cycles of the segments, which are padded to a power of two.
+    pub total_cycles: Option<u64>,
+}
+
+/// Returns the class of an RV32IM opcode.
+fn opcode_class(opcode: &str) -> &'static str {
+    match opcode {
+        "ADD" | "SUB" | "XOR" | "OR" | "AND" | "SLL" | "SRL" | "SRA" | "SLT" | "SLTU" | "LUI"
+        | "AUIPC" => "alu",
+        "MUL" | "MULH" | "MULHU" | "MULHSU" => "mul",
+        "DIV" | "DIVU" | "REM" | "REMU" => "div",
+        "LB" | "LH" | "LW" | "LBU" | "LHU" => "load",
+        "SB" | "SH" | "SW" => "store",
+        "BEQ" | "BNE" | "BLT" | "BGE" | "BLTU" | "BGEU" => "branch",
+        "JAL" | "JALR" => "jump",
+        "ECALL" | "EBREAK" => "system",
+        _ => "other",
+    }
+}
+
+impl ExecutionBreakdown {
+    /// Counts the instructions of an opcode, named in upper case, skipping opcodes never executed.
+    pub fn add_opcode(&mut self, opcode: &str, count: u64) {
+        if count == 0 {
+            return;
+        }
+        *self.opcodes.entry(opcode.to_string()).or_default() += count;
+        *self.instruction_classes.entry(opcode_class(opcode).to_string()).or_default() += count;
+    }
+
+    /// Counts the invocations of a syscall, named in upper case, skipping syscalls never invoked.
+    pub fn add_syscall(&mut self, syscall: &str, count: u64) {
+        if count == 0 {
+            return;
+        }
+        *self.syscalls.entry(syscall.to_string()).or_default() += count;
+        if !NON_PRECOMPILE_SYSCALLS.contains(&syscall) {
+            *self.precompile_invocations.get_or_insert(0) += count;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instructions_and_syscalls_are_classified() {
+        let mut breakdown = ExecutionBreakdown::default();
+        for (opcode, count) in [("ADD", 10), ("AUIPC", 1), ("LW", 4), ("SW", 2), ("DIVU", 0)] {
+            breakdown.add_opcode(opcode, count);
+        }
+        let syscalls = [("SHA_EXTEND", 3), ("SHA_COMPRESS", 3), ("WRITE", 5), ("HALT", 1)];
+        for (syscall, count) in syscalls {
+            breakdown.add_syscall(syscall, count);
+        }
+
+        let classes: Vec<_> =
+            breakdown.instruction_classes.iter().map(|(c, n)| (c.as_str(), *n)).collect();
+        assert_eq!(classes, [("alu", 11), ("load", 4), ("store", 2)]);
+        assert!(!breakdown.opcodes.contains_key("DIVU"));
+        assert_eq!(breakdown.syscalls.len(), 4);
+        assert_eq!(breakdown.precompile_invocations, Some(6));
+    }
+}
@@ -0,0 +1,285 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use crate::{
+    error::{EvalError, Result},
+    results::{read_rows, Row},
+};
+
+/// The metrics whose deltas are printed, lower being better for all of them.
+const METRICS: [&str; 12] = [
+    "cycles",
+    "shards",
+    "execution_duration_median",
+    "core_prove_duration_median",
+    "core_verify_duration_median",
+    "compress_prove_duration_median",
+    "compress_verify_duration_median",
+    "core_seal_size",
+    "core_proof_size",
+    "compress_seal_size",
+    "compress_proof_size",
+    "core_prove_peak_rss",
+];
+
+/// The thresholds used when none is given: any increase of cycles, or 5% of core proving time.
+const DEFAULT_THRESHOLDS: [(&str, f64); 2] = [("cycles", 0.0), ("core_prove_duration_median", 5.0)];
+
+/// The columns rows are joined on.
+const KEY_COLUMNS: [&str; 5] = ["program", "prover", "hashfn", "shard_size", "input_digest"];
+
+/// The arguments of the compare command.
+#[derive(clap::Args, Clone)]
+pub struct CompareArgs {
+    /// The result file to compare against, in CSV or JSONL.
+    baseline: PathBuf,
+    /// The result file to compare, in CSV or JSONL.
+    candidate: PathBuf,
+    /// The largest increase of a metric in percent that isn't a regression, e.g.
+    /// `core_prove_duration_median=5` (repeatable). Replaces the default thresholds, which fail on
+    /// any increase of cycles and on 5% more core proving time.
+    #[arg(long = "threshold", value_parser = parse_threshold)]
+    thresholds: Vec<Threshold>,
+}
+
+/// The largest increase of a metric that isn't a regression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Threshold {
+    /// The column of the metric.
+    metric: String,
+    /// The largest increase in percent of the baseline.
+    percent: f64,
+}
+
+/// Parses a threshold written as `metric=percent`.
+fn parse_threshold(value: &str) -> std::result::Result<Threshold, String> {
+    let (metric, percent) =
+        value.split_once('=').ok_or_else(|| format!("expected metric=percent, got {}", value))?;
+    let percent = percent.trim_end_matches('%').parse::<f64>().map_err(|e| e.to_string())?;
+    Ok(Threshold { metric: metric.to_string(), percent })
+}
+
+/// The change of a metric between the baseline and the candidate.
+struct Delta {
+    /// The column of the metric.
+    metric: String,
+    /// The value of the baseline.
+    baseline: f64,
+    /// The value of the candidate.
+    candidate: f64,
+    /// The change in percent of the baseline.
+    percent: f64,
+    /// The threshold of the metric, if it has one and it was exceeded.
+    exceeded: Option<f64>,
+}
+
+/// Returns the key a row is joined on, which is empty for the columns it doesn't have.
+fn key(row: &Row) -> Vec<String> {
+    KEY_COLUMNS.iter().map(|column| row.get(*column).cloned().unwrap_or_default()).collect()
+}
+
+/// Describes a key in the output, shortening the input digest.
+fn describe(key: &[String]) -> String {
+    let mut description = key[..4].join(", ");
+    if !key[4].is_empty() {
+        description += &format!(", input {}", &key[4][..key[4].len().min(8)]);
+    }
+    description
+}
+
+/// Returns the value of a numeric column, if the row has one.
+fn metric(row: &Row, column: &str) -> Option<f64> {
+    row.get(column).and_then(|value| value.parse().ok())
+}
+
+/// Indexes the rows by key, keeping the last row of each key.
+fn index(rows: Vec<Row>) -> BTreeMap<Vec<String>, Row> {
+    rows.into_iter().map(|row| (key(&row), row)).collect()
+}
+
+/// Returns the deltas of the printed and thresholded metrics of a pair of rows.
+///
+/// Metrics the baseline didn't measure (missing or zero, e.g. phases that didn't run) are skipped.
+fn deltas(baseline: &Row, candidate: &Row, thresholds: &[Threshold]) -> Vec<Delta> {
+    let mut metrics: Vec<&str> = METRICS.to_vec();
+    for threshold in thresholds {
+        if !metrics.contains(&threshold.metric.as_str()) {
+            metrics.push(&threshold.metric);
+        }
+    }
+
+    metrics
+        .into_iter()
+        .filter_map(|column| {
+            let base = metric(baseline, column).filter(|value| *value != 0.0)?;
+            let candidate = metric(candidate, column)?;
+            let percent = (candidate - base) / base * 100.0;
+            let exceeded = thresholds
+                .iter()
+                .find(|t| t.metric == column)
+                .map(|t| t.percent)
+                .filter(|limit| percent > *limit);
+            Some(Delta { metric: column.to_string(), baseline: base, candidate, percent, exceeded })
+        })
+        .collect()
+}
+
+/// Compares the rows of the candidate with the rows of the baseline they join with.
+///
+/// Returns the lines to print and the regressions found. A failed candidate row is a regression,
+/// while rows that only one side has are reported without failing.
+fn compare(
+    baseline: Vec<Row>,
+    candidate: Vec<Row>,
+    thresholds: &[Threshold],
+) -> (Vec<String>, Vec<String>) {
+    let baseline = index(baseline);
+    let candidate = index(candidate);
+    let mut lines = Vec::new();
+    let mut regressions = Vec::new();
+
+    for (key, candidate_row) in &candidate {
+        let description = describe(key);
+        let Some(baseline_row) = baseline.get(key) else {
+            lines.push(format!("{}: not in the baseline", description));
+            continue;
+        };
+        let status = |row: &Row| row.get("status").cloned().unwrap_or_else(|| "ok".to_string());
+        if status(candidate_row) != "ok" {
+            let error = candidate_row.get("error").cloned().unwrap_or_default();
+            lines.push(format!(
+                "{}: candidate failed with {}: {}",
+                description,
+                status(candidate_row),
+                error
+            ));
+            regressions.push(format!("{} failed", description));
+            continue;
+        }
+        if status(baseline_row) != "ok" {
+            lines.push(format!("{}: baseline failed, nothing to compare", description));
+            continue;
+        }
+
+        lines.push(format!("{}:", description));
+        for delta in deltas(baseline_row, candidate_row, thresholds) {
+            let mut line = format!(
+                "  {:<26} {:>16.3} {:>16.3} {:>+9.2}%",
+                delta.metric, delta.baseline, delta.candidate, delta.percent
+            );
+            if let Some(limit) = delta.exceeded {
+                line += &format!("  REGRESSION (> {}%)", limit);
+                regressions
+                    .push(format!("{} {} {:+.2}%", description, delta.metric, delta.percent));
+            }
+            lines.push(line);
+        }
+    }
+    for key in baseline.keys().filter(|key| !candidate.contains_key(*key)) {
+        lines.push(format!("{}: not in the candidate", describe(key)));
+    }
+    (lines, regressions)
+}
+
+/// Compares two result files and fails if a metric regressed beyond its threshold.
+pub fn run(args: &CompareArgs) -> Result<()> {
+    let thresholds = if args.thresholds.is_empty() {
+        DEFAULT_THRESHOLDS
+            .iter()
+            .map(|(metric, percent)| Threshold { metric: metric.to_string(), percent: *percent })
+            .collect()
+    } else {
+        args.thresholds.clone()
+    };
+
+    let baseline = read_rows(&args.baseline)?;
+    let candidate = read_rows(&args.candidate)?;
+    if let Some(threshold) = thresholds
+        .iter()
+        .find(|t| !candidate.iter().chain(&baseline).any(|row| row.contains_key(&t.metric)))
+    {
+        return Err(EvalError::Config(format!(
+            "no row has a {} column to apply the threshold to",
+            threshold.metric
+        )));
+    }
+
+    println!(
+        "  {:<26} {:>16} {:>16} {:>10}",
+        "metric",
+        args.baseline.display(),
+        args.candidate.display(),
+        "delta"
+    );
+    let (lines, regressions) = compare(baseline, candidate, &thresholds);
+    for line in lines {
+        println!("{}", line);
+    }
+
+    if regressions.is_empty() {
+        println!("No regressions");
+        Ok(())
+    } else {
+        Err(EvalError::Regression(regressions.join("; ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(program: &str, cycles: &str, core_prove_duration: &str, status: &str) -> Row {
+        [
+            ("program", program),
+            ("prover", "sp1"),
+            ("hashfn", "poseidon2"),
+            ("shard_size", "22"),
+            ("input_digest", "abc"),
+            ("cycles", cycles),
+            ("core_prove_duration_median", core_prove_duration),
+            ("status", status),
+        ]
+        .into_iter()
+        .map(|(column, value)| (column.to_string(), value.to_string()))
+        .collect()
+    }
+
+    fn thresholds() -> Vec<Threshold> {
+        vec![
+            parse_threshold("cycles=0").unwrap(),
+            parse_threshold("core_prove_duration_median=5%").unwrap(),
+        ]
+    }
+
+    #[test]
+    fn regressions_are_found_beyond_thresholds() {
+        let baseline =
+            vec![row("loop", "100", "10.0", "ok"), row("fibonacci", "100", "10.0", "ok")];
+
+        // Within the thresholds, and faster.
+        let candidate = vec![row("loop", "100", "10.4", "ok"), row("fibonacci", "90", "8.0", "ok")];
+        let (_, regressions) = compare(baseline.clone(), candidate, &thresholds());
+        assert!(regressions.is_empty());
+
+        // One more cycle, and 6% slower.
+        let candidate =
+            vec![row("loop", "101", "10.0", "ok"), row("fibonacci", "100", "10.6", "ok")];
+        let (_, regressions) = compare(baseline.clone(), candidate, &thresholds());
+        assert_eq!(regressions.len(), 2);
+
+        // A failed candidate is a regression, a missing one isn't.
+        let candidate = vec![row("loop", "0", "0", "proving_error")];
+        let (lines, regressions) = compare(baseline, candidate, &thresholds());
+        assert_eq!(regressions.len(), 1);
+        assert!(lines.iter().any(|line| line.contains("not in the candidate")));
+    }
+
+    #[test]
+    fn thresholds_are_parsed() {
+        assert_eq!(
+            parse_threshold("core_prove_duration_median=5").unwrap(),
+            Threshold { metric: "core_prove_duration_median".to_string(), percent: 5.0 }
+        );
+        assert!(parse_threshold("cycles").is_err());
+        assert!(parse_threshold("cycles=fast").is_err());
+    }
+}
@@ -0,0 +1,71 @@
+use std::fs;
+
+/// Metadata about the machine and toolchain an evaluation runs on.
+///
+/// Machine details are read from `/proc` and are left empty on platforms without procfs.
+#[derive(Debug, Default, Clone)]
+pub struct Environment {
+    /// The instance type, taken from the `INSTANCE_TYPE` environment variable.
+    pub instance_type: String,
+    /// The model name of the CPU.
+    pub cpu_model: String,
+    /// The number of CPU cores available to the process.
+    pub cpu_cores: usize,
+    /// Whether the CPU supports AVX-512.
+    pub avx512: bool,
+    /// The total memory of the machine in bytes.
+    pub total_memory: u64,
+    /// The release of the operating system kernel.
+    pub kernel: String,
+    /// The version of rustc the binary was built with.
+    pub rustc_version: String,
+    /// The resolved version of the SP1 prover crate.
+    pub sp1_version: String,
+    /// The resolved version of the RISC0 zkVM crate.
+    pub risc0_version: String,
+    /// The git commit the binary was built from.
+    pub git_sha: String,
+}
+
+impl Environment {
+    /// Collects the metadata of the current machine and binary.
+    pub fn current() -> Self {
+        let cpuinfo = fs::read_to_string("/proc/cpuinfo").unwrap_or_default();
+        let cpu_model = cpuinfo
+            .lines()
+            .find(|line| line.starts_with("model name"))
+            .and_then(|line| line.split_once(':'))
+            .map(|(_, model)| model.trim().to_string())
+            .unwrap_or_default();
+        let avx512 = cpuinfo
+            .lines()
+            .find(|line| line.starts_with("flags"))
+            .is_some_and(|flags| flags.split_whitespace().any(|flag| flag.starts_with("avx512")));
+
+        let total_memory = fs::read_to_string("/proc/meminfo")
+            .ok()
+            .and_then(|meminfo| {
+                let line = meminfo.lines().find(|line| line.starts_with("MemTotal:"))?;
+                line.split_whitespace().nth(1)?.parse::<u64>().ok()
+            })
+            .map(|kilobytes| kilobytes * 1024)
+            .unwrap_or_default();
+
+        let kernel = fs::read_to_string("/proc/sys/kernel/osrelease")
+            .map(|release| release.trim().to_string())
+            .unwrap_or_else(|_| std::env::consts::OS.to_string());
+
+        Self {
+            instance_type: std::env::var("INSTANCE_TYPE").unwrap_or_default(),
+            cpu_model,
+            cpu_cores: std::thread::available_parallelism().map(|n| n.get()).unwrap_or_default(),
+            avx512,
+            total_memory,
+            kernel,
+            rustc_version: env!("VERGEN_RUSTC_SEMVER").to_string(),
+            sp1_version: env!("EVAL_SP1_VERSION").to_string(),
+            risc0_version: env!("EVAL_RISC0_VERSION").to_string(),
+            git_sha: env!("VERGEN_GIT_SHA").to_string(),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+/// An error that stops the evaluation of a program.
+#[derive(thiserror::Error, Debug)]
+pub enum EvalError {
+    /// This error occurs when the evaluation is misconfigured (e.g. a missing ELF or input)
+    #[error("invalid configuration: {0}")]
+    Config(String),
+    /// This error occurs when the program fails to execute
+    #[error("failed to execute program: {0}")]
+    Execution(String),
+    /// This error occurs when the prover fails to generate a proof
+    #[error("failed to generate proof: {0}")]
+    Proving(String),
+    /// This error occurs when a proof is rejected by the verifier
+    #[error("failed to verify proof: {0}")]
+    Verification(String),
+    /// This error occurs when the guest program fails to build (e.g. a missing toolchain)
+    #[error("failed to build guest program: {0}")]
+    Build(String),
+    /// This error occurs when a compared metric regressed beyond its threshold
+    #[error("performance regressed: {0}")]
+    Regression(String),
+    /// This error occurs when an isolated run doesn't finish within its timeout
+    #[error("timed out: {0}")]
+    Timeout(String),
+    /// This error occurs when an isolated run runs out of memory (e.g. its memory limit)
+    #[error("ran out of memory: {0}")]
+    OutOfMemory(String),
+    /// This error occurs when an isolated run crashes (e.g. a panic or a signal)
+    #[error("crashed: {0}")]
+    Crash(String),
+}
+
+impl EvalError {
+    /// The value recorded in the `status` column of a failed run.
+    pub fn status(&self) -> &'static str {
+        match self {
+            EvalError::Config(_) => "config_error",
+            EvalError::Execution(_) => "execution_error",
+            EvalError::Proving(_) => "proving_error",
+            EvalError::Verification(_) => "verification_error",
+            EvalError::Build(_) => "build_error",
+            EvalError::Regression(_) => "regression",
+            EvalError::Timeout(_) => "timeout",
+            EvalError::OutOfMemory(_) => "oom",
+            EvalError::Crash(_) => "crash",
+        }
+    }
+
+    /// The process exit code used to report the error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            EvalError::Config(_) => 2,
+            EvalError::Execution(_) => 3,
+            EvalError::Proving(_) => 4,
+            EvalError::Verification(_) => 5,
+            EvalError::Build(_) => 6,
+            EvalError::Regression(_) => 7,
+            EvalError::Timeout(_) => 8,
+            EvalError::OutOfMemory(_) => 9,
+            EvalError::Crash(_) => 10,
+        }
+    }
+
+    /// Returns the error a process reported by exiting with the code, if it is one of theirs.
+    pub fn from_exit_code(code: i32, message: String) -> Option<Self> {
+        Some(match code {
+            2 => EvalError::Config(message),
+            3 => EvalError::Execution(message),
+            4 => EvalError::Proving(message),
+            5 => EvalError::Verification(message),
+            6 => EvalError::Build(message),
+            7 => EvalError::Regression(message),
+            8 => EvalError::Timeout(message),
+            9 => EvalError::OutOfMemory(message),
+            10 => EvalError::Crash(message),
+            _ => return None,
+        })
+    }
+}
+
+pub type Result<T> = core::result::Result<T, EvalError>;
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use sp1_reth_primitives::SP1RethInput;
+
+use crate::{
+    breakdown::ExecutionBreakdown,
+    error::{EvalError, Result},
+    inputs::InputValue,
+    programs::{GuestSpec, Sha2ChainInput},
+    proof_size::ProofSize,
+    timeline::ShardTiming,
+    EvalArgs, HashFnId, Mode, ProverId,
+};
+
+/// A value written to the guest program's input stream, in the order it is read.
+#[derive(Clone)]
+pub enum GuestInput {
+    /// The block input for the reth program.
+    Reth(Box<SP1RethInput>),
+    /// The parameters of the sha2-chain program.
+    Sha2Chain(Sha2ChainInput),
+    /// A buffer of bytes, read by the guest as a whole (e.g. the raw input of a Fuel fixture).
+    Bytes(Vec<u8>),
+    /// A value already serialized with bincode, passed to the guest as it is.
+    Bincode(Vec<u8>),
+    /// A typed value of a JSON input file.
+    Value(InputValue),
+}
+
+impl GuestInput {
+    /// The number of bytes the guest hashes for this input, if it is a hashing benchmark.
+    pub fn bytes_hashed(&self) -> Option<u64> {
+        match self {
+            GuestInput::Sha2Chain(input) => Some(input.bytes_hashed()),
+            _ => None,
+        }
+    }
+
+    /// The bytes of the input as written to the SP1 stdin: buffers as they are, and every other
+    /// input serialized with bincode.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let serialized = match self {
+            GuestInput::Reth(input) => bincode::serialize(input.as_ref()),
+            GuestInput::Sha2Chain(input) => bincode::serialize(input),
+            GuestInput::Value(value) => bincode::serialize(value),
+            GuestInput::Bytes(bytes) | GuestInput::Bincode(bytes) => return Ok(bytes.clone()),
+        };
+        serialized.map_err(|e| EvalError::Config(format!("failed to serialize input: {}", e)))
+    }
+}
+
+/// The guest program and its inputs.
+pub struct Program {
+    /// The ELF of the guest program, built for the selected prover.
+    pub elf: Vec<u8>,
+    /// The inputs written to the guest program before execution.
+    pub inputs: Vec<GuestInput>,
+}
+
+/// The outcome of executing the program without proving it.
+pub struct ExecutionOutput {
+    /// The reported number of cycles.
+    pub cycles: u64,
+    /// The number of shards (or segments) the execution will be split into when proving.
+    pub shards: usize,
+    /// The number of syscalls made by the program, if the zkVM reports it.
+    pub syscalls: Option<u64>,
+    /// What the execution spent its cycles on.
+    pub breakdown: ExecutionBreakdown,
+}
+
+/// The outcome of generating the core proof.
+pub struct CoreProofOutput {
+    /// The number of shards (or segments) the execution was split into.
+    pub shards: usize,
+    /// The size of the core proof.
+    pub proof_size: ProofSize,
+    /// What the prover reports of each shard (or segment), which the harness completes with the
+    /// spans of the prover.
+    pub shard_timings: Vec<ShardTiming>,
+}
+
+/// The outcome of compressing the core proof with recursion.
+pub struct CompressedProofOutput {
+    /// The size of the compressed proof.
+    pub proof_size: ProofSize,
+}
+
+/// A zkVM backend that can be driven through the phases of an evaluation.
+///
+/// The harness calls the phases in order and times each one, so an implementation should only do
+/// the work belonging to a phase inside of it. State produced by a phase (e.g. the proving key or
+/// the core proof) is kept on the evaluator for the phases that follow.
+///
+/// Failures are reported through [`EvalError`](crate::error::EvalError) rather than panics, so the
+/// harness can still record the run.
+pub trait Evaluator {
+    /// Prepares the prover and generates the proving and verifying keys for the program.
+    fn setup(&mut self, args: &EvalArgs, program: &Program) -> Result<()>;
+
+    /// Executes the program without proving it.
+    fn execute(&mut self) -> Result<ExecutionOutput>;
+
+    /// Generates the core proof of the last execution.
+    fn prove_core(&mut self) -> Result<CoreProofOutput>;
+
+    /// Verifies the core proof.
+    fn verify_core(&mut self) -> Result<()>;
+
+    /// Compresses the core proof with recursion.
+    fn compress(&mut self) -> Result<CompressedProofOutput>;
+
+    /// Verifies the compressed proof.
+    fn verify_compressed(&mut self) -> Result<()>;
+
+    /// Checks that tampered copies of the core proof and its public values fail to verify.
+    ///
+    /// Returns whether every tampered copy was rejected.
+    fn tamper_core(&mut self) -> bool;
+
+    /// Checks that tampered copies of the compressed proof and its public values fail to verify.
+    ///
+    /// Returns whether every tampered copy was rejected.
+    fn tamper_compressed(&mut self) -> bool;
+}
+
+/// A constructor for a fresh evaluator of a backend.
+pub type EvaluatorFactory = fn() -> Box<dyn Evaluator>;
+
+/// The options a backend supports, used to reject the configurations it can't run.
+#[derive(Clone)]
+pub struct Capabilities {
+    /// The hash functions the backend can prove with.
+    pub hashfns: Vec<HashSuite>,
+    /// Whether the shard size can be varied. If not, only the first shard size of a sweep is run.
+    pub shard_sizes: bool,
+    /// The version of the zkVM the backend proves with.
+    pub version: &'static str,
+}
+
+/// A hash function a backend can prove with, and how the backend configures it.
+#[derive(Clone)]
+pub struct HashSuite {
+    /// The hash function selected with `--hashfn`.
+    pub hashfn: HashFnId,
+    /// The name of the hash suite the backend proves with, which is recorded in the report.
+    pub name: &'static str,
+    /// Whether the core proof can be compressed when proving with this hash suite.
+    pub compress: bool,
+}
+
+impl Capabilities {
+    /// Returns the hash suite the evaluation proves with, or why the backend can't run it.
+    pub fn hash_suite(&self, args: &EvalArgs) -> std::result::Result<&HashSuite, String> {
+        let prover = args.prover.to_string();
+        let suite =
+            self.hashfns.iter().find(|suite| suite.hashfn == args.hashfn).ok_or_else(|| {
+                format!(
+                    "{} doesn't support the {} hash function, choose from: {}",
+                    prover,
+                    args.hashfn.to_string(),
+                    self.hashfns
+                        .iter()
+                        .map(|s| s.hashfn.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?;
+        if !suite.compress && matches!(args.mode, Mode::Compress | Mode::Full) {
+            return Err(format!(
+                "{} can't compress proofs made with {}, pass --mode core or execute",
+                prover, suite.name
+            ));
+        }
+        Ok(suite)
+    }
+
+    /// Checks that a guest pinned to a version of the zkVM is proven with that version, since the
+    /// prover can't run guests built against another one.
+    pub fn check_version(
+        &self,
+        args: &EvalArgs,
+        guest: &GuestSpec,
+    ) -> std::result::Result<(), String> {
+        let Some(pinned) = &guest.zkvm_version else { return Ok(()) };
+        let (pinned, version) =
+            (pinned.trim_start_matches('v'), self.version.trim_start_matches('v'));
+        if pinned != version {
+            let prover = args.prover.to_string();
+            return Err(format!(
+                "the {} guest of {} is built against {} {}, but this build proves with {} {}",
+                prover, args.program, prover, pinned, prover, version
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The evaluators available in this build, keyed by prover.
+pub struct Registry {
+    backends: HashMap<ProverId, (Capabilities, EvaluatorFactory)>,
+}
+
+impl Registry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self { backends: HashMap::new() }
+    }
+
+    /// Registers the evaluator used for a prover, replacing any previous one.
+    pub fn register(
+        &mut self,
+        prover: ProverId,
+        capabilities: Capabilities,
+        factory: EvaluatorFactory,
+    ) {
+        self.backends.insert(prover, (capabilities, factory));
+    }
+
+    /// Creates a fresh evaluator for a prover, if one is registered.
+    pub fn get(&self, prover: ProverId) -> Option<Box<dyn Evaluator>> {
+        self.backends.get(&prover).map(|(_, factory)| factory())
+    }
+
+    /// Returns the capabilities of the evaluator of a prover, if one is registered.
+    pub fn capabilities(&self, prover: ProverId) -> Option<&Capabilities> {
+        self.backends.get(&prover).map(|(capabilities, _)| capabilities)
+    }
+}
+
+impl Default for Registry {
+    /// Creates a registry with every backend enabled in this build.
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(ProverId::SP1, crate::sp1::SP1Evaluator::capabilities(), || {
+            Box::new(crate::sp1::SP1Evaluator::new())
+        });
+        #[cfg(feature = "risc0")]
+        registry.register(ProverId::Risc0, crate::risc0::Risc0Evaluator::capabilities(), || {
+            Box::new(crate::risc0::Risc0Evaluator::new())
+        });
+        registry
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{
+    error::{EvalError, Result},
+    programs::FixtureSet,
+};
+
+/// Returns the raw input of the fixture with the name, from the Fuel test fixtures.
+#[cfg(feature = "fuel")]
+pub fn get_fixture_input(set: FixtureSet, name: &str) -> Result<Vec<u8>> {
+    use fuel_zkvm_primitives_test_fixtures::{
+        block_execution_fixtures::fixtures as block_execution,
+        decompression_fixtures as decompression,
+    };
+
+    match set {
+        FixtureSet::BlockExecution => {
+            let fixture = find(block_execution::all_fixtures(), name)?;
+            Ok(block_execution::Fixture::get_input_for_fixture(&fixture))
+        }
+        FixtureSet::Decompression => {
+            let fixture = find(decompression::all_fixtures(), name)?;
+            Ok(decompression::Fixture::get_input_for_fixture(&fixture))
+        }
+    }
+}
+
+/// Returns an error, as the Fuel test fixtures are only available with the `fuel` feature.
+#[cfg(not(feature = "fuel"))]
+pub fn get_fixture_input(_set: FixtureSet, name: &str) -> Result<Vec<u8>> {
+    Err(EvalError::Config(format!(
+        "Fixture {} is not available. Please compile with --features fuel",
+        name
+    )))
+}
+
+/// Returns the fixture with the name, which is how the fixture is serialized.
+#[cfg(feature = "fuel")]
+fn find<F: serde::Serialize>(fixtures: Vec<F>, name: &str) -> Result<F> {
+    let names: Vec<String> = fixtures.iter().map(fixture_name).collect();
+    match names.iter().position(|n| n == name) {
+        Some(index) => Ok(fixtures.into_iter().nth(index).unwrap()),
+        None => Err(EvalError::Config(format!(
+            "Fixture {} not supported. Please choose from: {}",
+            name,
+            names.join(", ")
+        ))),
+    }
+}
+
+/// The name of a fixture, as its unit variant is serialized.
+#[cfg(feature = "fuel")]
+fn fixture_name<F: serde::Serialize>(fixture: &F) -> String {
+    match serde_json::to_value(fixture) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
@@ -0,0 +1,285 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::{EvalError, Result},
+    programs::ProgramRegistry,
+    utils::time_operation,
+    ProverId,
+};
+
+/// The flags the guest programs are compiled with, for every prover.
+const GUEST_RUSTFLAGS: &str = "-C passes=loweratomic -C link-arg=-Ttext=0x00200800 -C panic=abort";
+
+/// The arguments of the build command.
+#[derive(clap::Args, Clone)]
+pub struct BuildArgs {
+    /// The programs to build, separated by commas.
+    #[arg(long, value_delimiter = ',', required = true)]
+    programs: Vec<String>,
+    /// The provers to build the programs for, separated by commas.
+    #[arg(long, value_delimiter = ',', default_value = "sp1")]
+    provers: Vec<ProverId>,
+    /// Rebuild the programs even if their sources haven't changed.
+    #[arg(long)]
+    force: bool,
+}
+
+/// A guest program compiled for a prover.
+pub struct Guest {
+    /// The name of the program.
+    name: String,
+    /// The prover the program is built for.
+    prover: ProverId,
+    /// The cargo project of the guest program.
+    dir: PathBuf,
+    /// The package to build, if the project is a workspace with several of them.
+    package: Option<String>,
+    /// The features the guest is built with.
+    features: Vec<String>,
+    /// The path of the built ELF.
+    elf_path: PathBuf,
+}
+
+/// The ELF of a guest program, and how it was obtained.
+pub struct BuildOutput {
+    /// The ELF of the guest program.
+    pub elf: Vec<u8>,
+    /// The SHA-256 digest of the ELF, in hex.
+    pub elf_digest: String,
+    /// The time spent building the ELF, which is zero if an up to date ELF was reused.
+    pub duration: Duration,
+}
+
+/// What is known about the last build of a guest, stored next to its ELF.
+#[derive(Serialize, Deserialize)]
+struct BuildRecord {
+    /// The hash of the sources and lockfile the ELF was built from.
+    source_hash: String,
+    /// The SHA-256 digest of the ELF, in hex.
+    elf_digest: String,
+    /// The duration of the build in seconds.
+    build_duration: f64,
+}
+
+impl Guest {
+    /// Looks up the guest of the program for the prover in the registry.
+    pub fn new(programs: &ProgramRegistry, program: &str, prover: ProverId) -> Result<Self> {
+        let spec = programs.guest(program, prover)?;
+        let dir = programs.root().join(&spec.dir);
+        Ok(Self {
+            name: program.to_string(),
+            prover,
+            elf_path: dir.join(&spec.elf),
+            dir,
+            package: spec.package.clone(),
+            features: spec.features.clone(),
+        })
+    }
+
+    /// The target triple and rustup toolchain of the prover, and how to install the toolchain.
+    fn toolchain(&self) -> (&'static str, &'static str, &'static str) {
+        match self.prover {
+            ProverId::SP1 => ("riscv32im-succinct-zkvm-elf", "succinct", "sp1up"),
+            ProverId::Risc0 => ("riscv32im-risc0-zkvm-elf", "risc0", "rzup install"),
+        }
+    }
+
+    /// The path the ELF is built to.
+    pub fn elf_path(&self) -> &Path {
+        &self.elf_path
+    }
+
+    /// The path the record of the last build is stored at.
+    fn record_path(&self) -> PathBuf {
+        self.elf_path().with_extension("build.json")
+    }
+
+    /// Builds the ELF, unless the sources haven't changed since the last build.
+    pub fn build(&self, force: bool) -> Result<BuildOutput> {
+        if !self.dir.is_dir() {
+            return Err(EvalError::Config(format!(
+                "no guest program found at {}",
+                self.dir.display()
+            )));
+        }
+        let source_hash = hash_sources(&self.dir).map_err(|e| {
+            EvalError::Build(format!("failed to read sources of {}: {}", self.dir.display(), e))
+        })?;
+
+        // Reuse the ELF if it was built from the same sources and hasn't been modified since.
+        if !force {
+            if let Some(output) = self.cached(&source_hash) {
+                println!("{} for {} is up to date", self.name, self.prover.to_string());
+                return Ok(output);
+            }
+        }
+
+        self.check_toolchain()?;
+
+        // Build the program with the toolchain of the prover.
+        println!("Building {} for {}", self.name, self.prover.to_string());
+        let (target, toolchain, _) = self.toolchain();
+        let mut command = Command::new("cargo");
+        command.args(["build", "--release", "--ignore-rust-version"]);
+        if let Some(package) = &self.package {
+            command.args(["--package", package]);
+        }
+        if !self.features.is_empty() {
+            command.args(["--features", &self.features.join(",")]);
+        }
+        let (status, duration) = time_operation(|| {
+            command
+                .current_dir(&self.dir)
+                .env("RUSTFLAGS", GUEST_RUSTFLAGS)
+                .env("RUSTUP_TOOLCHAIN", toolchain)
+                .env("CARGO_BUILD_TARGET", target)
+                .env_remove("CARGO_TARGET_DIR")
+                .status()
+        });
+        let status = status.map_err(|e| EvalError::Build(format!("failed to run cargo: {}", e)))?;
+        if !status.success() {
+            return Err(EvalError::Build(format!(
+                "cargo build of {} for {} exited with {}",
+                self.name,
+                self.prover.to_string(),
+                status
+            )));
+        }
+
+        let mut output = self.load()?;
+        output.duration = duration;
+        let record = BuildRecord {
+            source_hash,
+            elf_digest: output.elf_digest.clone(),
+            build_duration: duration.as_secs_f64(),
+        };
+        fs::write(self.record_path(), serde_json::to_vec_pretty(&record).unwrap()).map_err(
+            |e| EvalError::Build(format!("failed to write build record of {}: {}", self.name, e)),
+        )?;
+        Ok(output)
+    }
+
+    /// Reads the ELF as it was last built, without building it.
+    pub fn load(&self) -> Result<BuildOutput> {
+        let elf = fs::read(&self.elf_path).map_err(|e| {
+            EvalError::Config(format!("failed to read elf {}: {}", self.elf_path.display(), e))
+        })?;
+        let elf_digest = hex::encode(Sha256::digest(&elf));
+        Ok(BuildOutput { elf, elf_digest, duration: Duration::ZERO })
+    }
+
+    /// Returns the ELF if it was last built from sources with the hash and is unchanged since.
+    fn cached(&self, source_hash: &str) -> Option<BuildOutput> {
+        let record = fs::read(self.record_path()).ok()?;
+        let record: BuildRecord = serde_json::from_slice(&record).ok()?;
+        let output = self.load().ok()?;
+        (record.source_hash == source_hash && record.elf_digest == output.elf_digest)
+            .then_some(output)
+    }
+
+    /// Checks that the rustup toolchain of the prover is installed.
+    fn check_toolchain(&self) -> Result<()> {
+        let (_, toolchain, install) = self.toolchain();
+        let output = Command::new("rustup")
+            .args(["toolchain", "list"])
+            .output()
+            .map_err(|e| EvalError::Build(format!("failed to run rustup: {}", e)))?;
+        let installed = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .any(|line| line.split_whitespace().next() == Some(toolchain));
+        if !installed {
+            return Err(EvalError::Build(format!(
+                "the {} toolchain is not installed, install it with `{}`",
+                toolchain, install
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Hashes the files of a cargo project, skipping its build outputs.
+///
+/// The hash covers the relative path and contents of every file, so it changes when any source,
+/// manifest or lockfile is added, removed or modified.
+fn hash_sources(dir: &Path) -> std::io::Result<String> {
+    let mut files = Vec::new();
+    collect_files(dir, &mut files)?;
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    for file in files {
+        let relative = file.strip_prefix(dir).unwrap_or(&file);
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        let contents = fs::read(&file)?;
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Collects the files under the directory, except in `target` and hidden directories.
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            let name = entry.file_name();
+            if name != "target" && !name.to_string_lossy().starts_with('.') {
+                collect_files(&path, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Builds every program for every prover, reusing the ELFs that are up to date.
+pub fn run(args: &BuildArgs, programs: &ProgramRegistry) -> Result<()> {
+    for program in &args.programs {
+        for &prover in &args.provers {
+            let output = Guest::new(programs, program, prover)?.build(args.force)?;
+            println!(
+                "{} for {}: {} ({:.2}s)",
+                program,
+                prover.to_string(),
+                output.elf_digest,
+                output.duration.as_secs_f64()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_hash_tracks_sources_but_not_build_outputs() {
+        let dir = std::env::temp_dir().join(format!("eval-{}-guest", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("target")).unwrap();
+        fs::write(dir.join("Cargo.lock"), "version = 3").unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+        let hash = hash_sources(&dir).unwrap();
+
+        fs::write(dir.join("target/guest"), "elf").unwrap();
+        assert_eq!(hash_sources(&dir).unwrap(), hash);
+
+        fs::write(dir.join("Cargo.lock"), "version = 4").unwrap();
+        assert_ne!(hash_sources(&dir).unwrap(), hash);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,362 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    blocks::gas_used,
+    error::{EvalError, Result},
+    evaluator::{Evaluator, GuestInput, Program, Registry},
+    guest::Guest,
+    inputs::digest_inputs,
+    isolation::RECORDED_TRIALS,
+    monitor::{measure_operation, ResourceUsage},
+    programs::ProgramRegistry,
+    results::{append, check_compatible, result_paths, CompanionRecord, PerformanceReport, Sample},
+    timeline::{record_spans, Timeline},
+    utils::{get_inputs, with_env},
+    EvalArgs, Mode, ProverMode,
+};
+
+/// Returns the name of the result files of the filename, which are kept apart for every commit.
+pub fn result_stem(filename: &str) -> String {
+    format!("{}_{}", filename, env!("VERGEN_GIT_SHA"))
+}
+
+/// Runs an evaluation and appends its report and samples to the result files in the directory.
+///
+/// A failed run still appends its rows, with the failure recorded in the report. Only when the
+/// result files can't be appended to is nothing run or written, and rows that fail to be written
+/// fail the evaluation with a configuration error.
+pub fn evaluate(
+    args: &EvalArgs,
+    registry: &Registry,
+    programs: &ProgramRegistry,
+    results_dir: &Path,
+) -> Result<()> {
+    // Check that the result files can be appended to before running the evaluation.
+    let files = ResultFiles::checked(args, results_dir)?;
+
+    let mut report = PerformanceReport::new(args);
+
+    // Select the correct implementation based on the prover, check that it supports the
+    // configuration and run it.
+    let result = match (registry.get(args.prover), registry.capabilities(args.prover)) {
+        (Some(mut evaluator), Some(capabilities)) => {
+            capabilities.hash_suite(args).map_err(EvalError::Config).and_then(|suite| {
+                report.hashfn = suite.name.to_string();
+                let guest = programs.guest(&args.program, args.prover)?;
+                capabilities.check_version(args, guest).map_err(EvalError::Config)?;
+                run(args, programs, evaluator.as_mut(), &mut report)
+            })
+        }
+        _ => Err(EvalError::Config(format!(
+            "No evaluator is registered for {}. Please compile with --features {}",
+            args.prover.to_string(),
+            args.prover.to_string()
+        ))),
+    };
+
+    // Record the failure in the report instead of aborting, so the run still gets a row.
+    if let Err(err) = &result {
+        report.fail(err);
+    }
+
+    // Report the failure of the run over the failure to write its rows, but don't hide either.
+    let written = files.write(&report, results_dir);
+    if let (Err(_), Err(err)) = (&result, &written) {
+        eprintln!("Error: {}", err);
+    }
+
+    // Tell a sweep running the evaluation in a child process which trials it doesn't have to run
+    // again.
+    if written.is_ok() {
+        eprintln!("{}{}", RECORDED_TRIALS, report.completed_trials());
+    }
+    result.and(written)
+}
+
+/// Records the failure of an evaluation that couldn't write its own rows, e.g. because it ran in
+/// a process that timed out or crashed.
+pub fn record_failure(
+    args: &EvalArgs,
+    registry: &Registry,
+    results_dir: &Path,
+    err: &EvalError,
+) -> Result<()> {
+    let files = ResultFiles::checked(args, results_dir)?;
+    let mut report = PerformanceReport::new(args);
+    // Name the hash function by its suite, as the row of a run that failed on its own would.
+    if let Some(Ok(suite)) = registry.capabilities(args.prover).map(|c| c.hash_suite(args)) {
+        report.hashfn = suite.name.to_string();
+    }
+    report.fail(err);
+    files.write(&report, results_dir)
+}
+
+/// The files the rows of an evaluation are appended to.
+struct ResultFiles {
+    /// The report files, one for every extension of the format.
+    reports: Vec<PathBuf>,
+    /// The sample files, one for every extension of the format.
+    samples: Vec<PathBuf>,
+    /// The JSONL file of the execution breakdowns, which is written in every format.
+    breakdowns: PathBuf,
+    /// The JSONL file of the shard timelines, which is written in every format.
+    timelines: PathBuf,
+}
+
+impl ResultFiles {
+    /// Returns the result files of the evaluation, once it is checked that they can be appended
+    /// to.
+    fn checked(args: &EvalArgs, results_dir: &Path) -> Result<Self> {
+        let stem = result_stem(&args.filename);
+        let files = Self {
+            reports: result_paths(results_dir, &stem, args.format),
+            samples: result_paths(results_dir, &format!("{}_samples", stem), args.format),
+            breakdowns: results_dir.join(format!("{}_breakdowns.jsonl", stem)),
+            timelines: results_dir.join(format!("{}_timelines.jsonl", stem)),
+        };
+        for path in &files.reports {
+            check_compatible::<PerformanceReport>(path)?;
+        }
+        for path in &files.samples {
+            check_compatible::<Sample>(path)?;
+        }
+        Ok(files)
+    }
+
+    /// Appends the report, its samples, its execution breakdown and its timeline to the files.
+    fn write(&self, report: &PerformanceReport, results_dir: &Path) -> Result<()> {
+        let failed = |path: &Path, e: std::io::Error| {
+            EvalError::Config(format!("failed to write results {}: {}", path.display(), e))
+        };
+
+        // Write the samples of every trial.
+        for path in &self.samples {
+            append(path, &report.samples).map_err(|e| failed(path, e))?;
+        }
+
+        // Write the breakdown of the execution and the timeline of the core proof, if the run got
+        // that far.
+        if let Some(breakdown) = &report.breakdown {
+            append(&self.breakdowns, &[CompanionRecord::new(report, breakdown)])
+                .map_err(|e| failed(&self.breakdowns, e))?;
+        }
+        if let Some(timeline) = &report.timeline {
+            append(&self.timelines, &[CompanionRecord::new(report, timeline)])
+                .map_err(|e| failed(&self.timelines, e))?;
+        }
+
+        // Write the report row and keep a copy of the whole file as the latest results.
+        for path in &self.reports {
+            append(path, std::slice::from_ref(report)).map_err(|e| failed(path, e))?;
+
+            let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+            let latest_path = results_dir.join(format!("benchmarks_latest.{}", extension));
+            fs::copy(path, &latest_path).map_err(|e| failed(&latest_path, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Drives an evaluator through every phase and fills in the performance report.
+///
+/// Setup runs once, and the keys it produces are reused by the warmup and measured trials that
+/// follow. If requested, the tamper check runs in the last trial, outside of the timed phases. The
+/// report is updated as each phase completes, so on failure it still holds the
+/// measurements of the phases that succeeded.
+pub fn run(
+    args: &EvalArgs,
+    programs: &ProgramRegistry,
+    evaluator: &mut dyn Evaluator,
+    report: &mut PerformanceReport,
+) -> Result<()> {
+    if args.trials == 0 {
+        return Err(EvalError::Config("At least one trial is required".to_string()));
+    }
+    if args.tamper_check && args.prover_mode == ProverMode::Mock {
+        return Err(EvalError::Config("The tamper check requires real proofs".to_string()));
+    }
+
+    // Build the program, unless the prebuilt ELF is used, and load its inputs.
+    let spec = programs.get(&args.program)?;
+    let guest = Guest::new(programs, &args.program, args.prover)?;
+    let build = if args.skip_build { guest.load()? } else { guest.build(false)? };
+    println!("elf path: {}", guest.elf_path().display());
+    report.elf_digest = build.elf_digest;
+    report.build_duration = build.duration.as_secs_f64();
+    let program = Program { elf: build.elf, inputs: get_inputs(args, spec.input)? };
+    report.bytes_hashed = program.inputs.iter().find_map(GuestInput::bytes_hashed);
+    report.gas_used = args.block_number.and_then(gas_used);
+    (report.input_digest, report.input_size) = digest_inputs(&program.inputs)?;
+
+    // Configure the prover for the program while it runs.
+    let env = &programs.guest(&args.program, args.prover)?.env;
+    with_env(env, || run_trials(args, evaluator, report, &program))
+}
+
+/// Sets up the program and runs the warmup and measured trials.
+fn run_trials(
+    args: &EvalArgs,
+    evaluator: &mut dyn Evaluator,
+    report: &mut PerformanceReport,
+    program: &Program,
+) -> Result<()> {
+    // Setup the program.
+    evaluator.setup(args, program)?;
+
+    // Run the warmup trials followed by the measured ones.
+    let total_trials = args.warmup + args.trials;
+    let mock = args.prover_mode == ProverMode::Mock;
+    for trial in 0..total_trials {
+        let mut sample = report.sample(trial);
+        let tamper_check = args.tamper_check && trial + 1 == total_trials;
+        let result =
+            run_trial(args.mode, mock, tamper_check, args.timeline, evaluator, report, &mut sample);
+        report.samples.push(sample);
+        report.summarize();
+        result?;
+    }
+
+    Ok(())
+}
+
+/// Runs the phases of the mode after setup once, recording the durations in the sample and, if
+/// requested, the timeline of the core proof in the report.
+///
+/// Mock proofs are generated and verified like real ones, but the measurements of those phases are
+/// zeroed, since they say nothing about proving.
+fn run_trial(
+    mode: Mode,
+    mock: bool,
+    tamper_check: bool,
+    timeline: bool,
+    evaluator: &mut dyn Evaluator,
+    report: &mut PerformanceReport,
+    sample: &mut Sample,
+) -> Result<()> {
+    // Execute the program.
+    let (execution, execution_duration, usage) = measure_operation(|| evaluator.execute());
+    let execution = execution?;
+    report.cycles = execution.cycles;
+    report.shards = execution.shards;
+    report.syscalls = execution.syscalls;
+    report.breakdown = Some(execution.breakdown);
+    sample.execution_duration = Some(execution_duration.as_secs_f64());
+    record_usage(
+        usage,
+        &mut sample.execution_peak_rss,
+        &mut sample.execution_cpu_time,
+        &mut sample.execution_cpu_cores,
+    );
+    if mode == Mode::Execute {
+        return Ok(());
+    }
+
+    // Generate and verify the core proof, recording the spans of the prover for the timeline.
+    let ((core, spans), core_prove_duration, usage) = measure_proof(mock, || {
+        if timeline {
+            record_spans(|| evaluator.prove_core())
+        } else {
+            (evaluator.prove_core(), Vec::new())
+        }
+    });
+    let core = core?;
+    if timeline {
+        report.timeline = Some(Timeline::new(core.shard_timings, spans));
+    }
+    report.shards = core.shards;
+    report.core_seal_size = core.proof_size.seal;
+    report.core_proof_size = core.proof_size.receipt;
+    report.core_public_values_size = core.proof_size.public_values;
+    sample.core_prove_duration = Some(core_prove_duration.as_secs_f64());
+    sample.prove_duration = Some(core_prove_duration.as_secs_f64());
+    record_usage(
+        usage,
+        &mut sample.core_prove_peak_rss,
+        &mut sample.core_prove_cpu_time,
+        &mut sample.core_prove_cpu_cores,
+    );
+
+    let (verified, core_verify_duration, usage) = measure_proof(mock, || evaluator.verify_core());
+    verified?;
+    sample.core_verify_duration = Some(core_verify_duration.as_secs_f64());
+    record_usage(
+        usage,
+        &mut sample.core_verify_peak_rss,
+        &mut sample.core_verify_cpu_time,
+        &mut sample.core_verify_cpu_cores,
+    );
+    if tamper_check {
+        record_tamper_check(report, "core", evaluator.tamper_core())?;
+    }
+    if mode == Mode::Core {
+        return Ok(());
+    }
+
+    // Compress the proof with recursion and verify it.
+    let (compressed, compress_duration, usage) = measure_proof(mock, || evaluator.compress());
+    let compressed = compressed?;
+    report.compress_seal_size = compressed.proof_size.seal;
+    report.compress_proof_size = compressed.proof_size.receipt;
+    report.compress_public_values_size = compressed.proof_size.public_values;
+    sample.compress_prove_duration = Some(compress_duration.as_secs_f64());
+    sample.prove_duration = Some((core_prove_duration + compress_duration).as_secs_f64());
+    record_usage(
+        usage,
+        &mut sample.compress_prove_peak_rss,
+        &mut sample.compress_prove_cpu_time,
+        &mut sample.compress_prove_cpu_cores,
+    );
+    if mode == Mode::Compress {
+        return Ok(());
+    }
+
+    let (verified, compress_verify_duration, usage) =
+        measure_proof(mock, || evaluator.verify_compressed());
+    verified?;
+    sample.compress_verify_duration = Some(compress_verify_duration.as_secs_f64());
+    record_usage(
+        usage,
+        &mut sample.compress_verify_peak_rss,
+        &mut sample.compress_verify_cpu_time,
+        &mut sample.compress_verify_cpu_cores,
+    );
+    if tamper_check {
+        record_tamper_check(report, "compressed", evaluator.tamper_compressed())?;
+    }
+
+    Ok(())
+}
+
+/// Measures a phase that generates or verifies a proof, zeroing the measurements of mock proofs.
+fn measure_proof<T>(mock: bool, operation: impl FnOnce() -> T) -> (T, Duration, ResourceUsage) {
+    let (output, duration, usage) = measure_operation(operation);
+    if mock {
+        return (output, Duration::ZERO, ResourceUsage::default());
+    }
+    (output, duration, usage)
+}
+
+/// Records whether the tampered copies of a proof were rejected, failing the run if any wasn't.
+fn record_tamper_check(report: &mut PerformanceReport, proof: &str, rejected: bool) -> Result<()> {
+    report.tamper_check = Some(report.tamper_check.unwrap_or(true) && rejected);
+    if !rejected {
+        return Err(EvalError::Verification(format!("a tampered {} proof was accepted", proof)));
+    }
+    Ok(())
+}
+
+/// Records the resource usage of a phase in the sample columns of that phase.
+fn record_usage(
+    usage: ResourceUsage,
+    peak_rss: &mut Option<u64>,
+    cpu_time: &mut Option<f64>,
+    cpu_cores: &mut Option<f64>,
+) {
+    *peak_rss = Some(usage.peak_rss);
+    *cpu_time = Some(usage.cpu_time);
+    *cpu_cores = Some(usage.cpu_cores);
+}
@@ -0,0 +1,140 @@
+use std::{fs, path::Path};
+
+use serde::{ser::SerializeTuple, Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::{EvalError, Result},
+    evaluator::GuestInput,
+};
+
+/// A typed value of a JSON input file, serialized the way the guest reads it.
+///
+/// JSON doesn't say how wide a number is or whether an array is a vector or a struct, so every
+/// value is tagged with its type, e.g. `{"tuple": [{"u32": 1000}, {"bool": true}]}` for a struct
+/// of a `u32` and a `bool`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum InputValue {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I32(i32),
+    I64(i64),
+    String(String),
+    /// A byte vector, written in hex with an optional `0x` prefix.
+    Bytes(#[serde(deserialize_with = "hex_bytes")] Vec<u8>),
+    /// A vector of values, serialized with its length.
+    Vec(Vec<InputValue>),
+    /// A tuple or struct of values, serialized in order without a length.
+    Tuple(Vec<InputValue>),
+}
+
+impl Serialize for InputValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            InputValue::Bool(value) => value.serialize(serializer),
+            InputValue::U8(value) => value.serialize(serializer),
+            InputValue::U16(value) => value.serialize(serializer),
+            InputValue::U32(value) => value.serialize(serializer),
+            InputValue::U64(value) => value.serialize(serializer),
+            InputValue::I32(value) => value.serialize(serializer),
+            InputValue::I64(value) => value.serialize(serializer),
+            InputValue::String(value) => value.serialize(serializer),
+            InputValue::Bytes(value) => value.serialize(serializer),
+            InputValue::Vec(values) => values.serialize(serializer),
+            InputValue::Tuple(values) => {
+                let mut tuple = serializer.serialize_tuple(values.len())?;
+                for value in values {
+                    tuple.serialize_element(value)?;
+                }
+                tuple.end()
+            }
+        }
+    }
+}
+
+/// Deserializes a byte vector from hex.
+fn hex_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<u8>, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    hex::decode(hex.trim_start_matches("0x")).map_err(serde::de::Error::custom)
+}
+
+/// Reads an input file passed with `--input`, in the format given by its extension.
+///
+/// - `.json`: a typed [`InputValue`], serialized by each prover the way the guest reads it.
+/// - `.bin` or `.bincode`: a value serialized with bincode, written to RISC0 guests after its
+///   length.
+/// - anything else: raw bytes, read by the guest as a byte vector.
+pub fn load_input(path: &Path) -> Result<GuestInput> {
+    let bytes = fs::read(path).map_err(|e| {
+        EvalError::Config(format!("failed to read input {}: {}", path.display(), e))
+    })?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => serde_json::from_slice(&bytes).map(GuestInput::Value).map_err(|e| {
+            EvalError::Config(format!("invalid JSON input {}: {}", path.display(), e))
+        }),
+        Some("bin" | "bincode") => Ok(GuestInput::Bincode(bytes)),
+        _ => Ok(GuestInput::Bytes(bytes)),
+    }
+}
+
+/// Returns the SHA-256 digest, in hex, and the size in bytes of the inputs.
+///
+/// Both cover the inputs as written to the SP1 stdin, one after the other, so the digest of a
+/// single raw or bincode file is the digest of the file.
+pub fn digest_inputs(inputs: &[GuestInput]) -> Result<(String, u64)> {
+    let mut hasher = Sha256::new();
+    let mut size = 0;
+    for input in inputs {
+        let bytes = input.to_bytes()?;
+        hasher.update(&bytes);
+        size += bytes.len() as u64;
+    }
+    Ok((hex::encode(hasher.finalize()), size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_values_serialize_like_the_types_they_describe() {
+        let json = r#"{"tuple": [{"u32": 1000}, {"bool": true}, {"bytes": "0x0102"}]}"#;
+        let value: InputValue = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            bincode::serialize(&value).unwrap(),
+            bincode::serialize(&(1000u32, true, vec![1u8, 2])).unwrap()
+        );
+
+        let vec: InputValue = serde_json::from_str(r#"{"vec": [{"u64": 1}, {"u64": 2}]}"#).unwrap();
+        assert_eq!(bincode::serialize(&vec).unwrap(), bincode::serialize(&vec![1u64, 2]).unwrap());
+
+        assert!(serde_json::from_str::<InputValue>(r#"{"u8": 256}"#).is_err());
+        assert!(serde_json::from_str::<InputValue>("1000").is_err());
+    }
+
+    #[test]
+    fn input_files_are_read_by_extension() {
+        let dir = std::env::temp_dir().join(format!("eval-{}-inputs", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("input.json"), r#"{"u32": 7}"#).unwrap();
+        fs::write(dir.join("input.bin"), [7, 0, 0, 0]).unwrap();
+        fs::write(dir.join("input.txt"), "raw").unwrap();
+
+        let json = load_input(&dir.join("input.json")).unwrap();
+        let bincode = load_input(&dir.join("input.bin")).unwrap();
+        assert!(matches!(json, GuestInput::Value(InputValue::U32(7))));
+        assert!(matches!(bincode, GuestInput::Bincode(_)));
+        assert!(matches!(load_input(&dir.join("input.txt")).unwrap(), GuestInput::Bytes(_)));
+        assert!(load_input(&dir.join("missing.json")).is_err());
+
+        // Both are written to the SP1 stdin as the same bytes.
+        assert_eq!(digest_inputs(&[json]).unwrap(), digest_inputs(&[bincode]).unwrap());
+        assert_eq!(digest_inputs(&[]).unwrap().1, 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
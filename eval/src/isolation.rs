@@ -0,0 +1,253 @@
+use std::{
+    env,
+    io::{BufRead, BufReader},
+    os::unix::process::{CommandExt, ExitStatusExt},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+
+use crate::{
+    error::{EvalError, Result},
+    EvalArgs,
+};
+
+/// How often the child process is checked for having exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The line the standard library writes before aborting when an allocation fails.
+const ALLOCATION_FAILURE: &str = "memory allocation of";
+
+/// The prefix of the line an evaluation writes once its rows are written, followed by the number
+/// of measured trials that completed.
+pub const RECORDED_TRIALS: &str = "Recorded trials: ";
+
+/// The limits of an evaluation run in a child process.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Limits {
+    /// The wall-clock time after which the child is killed.
+    pub timeout: Option<Duration>,
+    /// The size of the address space of the child in bytes.
+    pub memory: Option<u64>,
+}
+
+/// What the child process reported on its standard error.
+#[derive(Default)]
+struct Output {
+    /// The error the child exited with, without the prefix of its kind.
+    error: Option<String>,
+    /// The message of the panic the child crashed with.
+    panic: Option<String>,
+    /// Whether an allocation of the child failed.
+    allocation_failed: bool,
+    /// The number of measured trials the child completed and wrote the rows of.
+    recorded_trials: usize,
+}
+
+/// The outcome of an evaluation run in a child process.
+pub struct Isolated {
+    /// The number of measured trials whose rows the child wrote, which a failed evaluation may
+    /// have completed some of before it failed.
+    pub recorded_trials: usize,
+    /// The error the child reported, or how it ended if it didn't report one.
+    pub result: Result<()>,
+}
+
+/// Returns the name of a value on the command line.
+fn name<T: ValueEnum>(value: &T) -> String {
+    value.to_possible_value().unwrap().get_name().to_string()
+}
+
+/// Returns the arguments of the `run` command that evaluates the arguments.
+pub fn eval_command(args: &EvalArgs) -> Vec<String> {
+    let mut command: Vec<String> = [
+        "run",
+        "--program",
+        &args.program,
+        "--prover",
+        &name(&args.prover),
+        "--hashfn",
+        &name(&args.hashfn),
+        "--shard-size",
+        &args.shard_size.to_string(),
+        "--filename",
+        &args.filename,
+        "--trials",
+        &args.trials.to_string(),
+        "--warmup",
+        &args.warmup.to_string(),
+        "--mode",
+        &name(&args.mode),
+        "--prover-mode",
+        &name(&args.prover_mode),
+        "--format",
+        &name(&args.format),
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+    if let Some(block_number) = args.block_number {
+        command.extend(["--block-number".to_string(), block_number.to_string()]);
+    }
+    if let Some(fixture) = &args.fixture {
+        command.extend(["--fixture".to_string(), fixture.clone()]);
+    }
+    for input in &args.inputs {
+        command.extend(["--input".to_string(), input.display().to_string()]);
+    }
+    if args.tamper_check {
+        command.push("--tamper-check".to_string());
+    }
+    if args.timeline {
+        command.push("--timeline".to_string());
+    }
+    if args.skip_build {
+        command.push("--skip-build".to_string());
+    }
+    command
+}
+
+/// Runs the evaluation in a child process of this binary, which appends its own rows to the result
+/// files, and returns the error it reported along with the trials it recorded.
+///
+/// A child that didn't report an error of its own is classified by how it ended: killed after the
+/// timeout, out of memory if an allocation failed or the kernel killed it, or crashed otherwise.
+/// The rows of those runs are left to the caller to record, and none of their trials are. On
+/// timeout, the processes the child started are killed along with it.
+pub fn evaluate_isolated(args: &EvalArgs, limits: Limits) -> Isolated {
+    let mut output = Output::default();
+    let result = run_child(args, limits, &mut output);
+    Isolated { recorded_trials: output.recorded_trials, result }
+}
+
+/// Runs the evaluation in a child process, filling in what it reported on its standard error.
+fn run_child(args: &EvalArgs, limits: Limits, output: &mut Output) -> Result<()> {
+    let executable = env::current_exe()
+        .map_err(|e| EvalError::Config(format!("failed to find the eval binary: {}", e)))?;
+    let mut command = Command::new(executable);
+    // Run the child in a process group of its own, so that the processes it starts are killed
+    // with it on timeout.
+    command.args(eval_command(args)).stderr(Stdio::piped()).process_group(0);
+    if let Some(memory) = limits.memory {
+        let limit =
+            libc::rlimit { rlim_cur: memory as libc::rlim_t, rlim_max: memory as libc::rlim_t };
+        // SAFETY: setrlimit is async-signal-safe, and nothing else runs between fork and exec.
+        unsafe {
+            command.pre_exec(move || {
+                if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| EvalError::Config(format!("failed to start the eval binary: {}", e)))?;
+
+    // Forward the standard error of the child while looking for how it failed.
+    let stderr = child.stderr.take().unwrap();
+    let reader = thread::spawn(move || {
+        let mut output = Output::default();
+        for line in BufReader::new(stderr).lines().map_while(|line| line.ok()) {
+            eprintln!("{}", line);
+            if let Some(error) = line.strip_prefix("Error: ") {
+                output.error = Some(error.to_string());
+            } else if line.contains("panicked at") {
+                output.panic = Some(line.clone());
+            } else if line.contains(ALLOCATION_FAILURE) {
+                output.allocation_failed = true;
+            } else if let Some(trials) = line.strip_prefix(RECORDED_TRIALS) {
+                output.recorded_trials = trials.parse().unwrap_or_default();
+            }
+        }
+        output
+    });
+
+    let start = Instant::now();
+    let mut timed_out = false;
+    let status = loop {
+        let wait_error = |e: std::io::Error| EvalError::Crash(format!("failed to wait: {}", e));
+        if let Some(status) = child.try_wait().map_err(wait_error)? {
+            break status;
+        }
+        if limits.timeout.is_some_and(|timeout| start.elapsed() > timeout) {
+            timed_out = true;
+            // SAFETY: kill has no memory safety requirements, and the group is the child's own.
+            unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+            break child.wait().map_err(wait_error)?;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    *output = reader.join().unwrap_or_default();
+
+    if status.success() {
+        return Ok(());
+    }
+    if timed_out {
+        return Err(EvalError::Timeout(format!(
+            "killed after {}s",
+            limits.timeout.unwrap().as_secs()
+        )));
+    }
+    if let Some(error) = status.code().and_then(|code| {
+        // Strip the prefix of the error's kind, which the error adds back when displayed.
+        let prefix = EvalError::from_exit_code(code, String::new())?.to_string();
+        let message = output.error.as_deref().unwrap_or("");
+        EvalError::from_exit_code(code, message.strip_prefix(&prefix).unwrap_or(message).into())
+    }) {
+        return Err(error);
+    }
+    match status.signal() {
+        _ if output.allocation_failed => {
+            Err(EvalError::OutOfMemory("an allocation failed".to_string()))
+        }
+        Some(libc::SIGKILL) => Err(EvalError::OutOfMemory(
+            "killed by SIGKILL, most likely by the kernel's OOM killer".to_string(),
+        )),
+        Some(signal) => Err(EvalError::Crash(format!("killed by signal {}", signal))),
+        None => Err(EvalError::Crash(
+            output.panic.take().unwrap_or_else(|| format!("exited with {}", status)),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ledger::config_hash, Cli, Command, HashFnId, Mode, OutputFormat, ProverId, ProverMode,
+    };
+    use clap::Parser;
+
+    #[test]
+    fn eval_commands_parse_back_to_the_arguments() {
+        let args = EvalArgs {
+            program: "reth".to_string(),
+            prover: ProverId::SP1,
+            hashfn: HashFnId::Sha256,
+            shard_size: 22,
+            filename: "benchmark".to_string(),
+            block_number: Some(17106222),
+            fixture: Some("a".to_string()),
+            inputs: vec!["input.json".into()],
+            trials: 3,
+            warmup: 1,
+            mode: Mode::Core,
+            prover_mode: ProverMode::Mock,
+            tamper_check: true,
+            timeline: true,
+            skip_build: true,
+            format: OutputFormat::Both,
+        };
+
+        let cli = Cli::try_parse_from(["eval".to_string()].into_iter().chain(eval_command(&args)))
+            .unwrap();
+        let Command::Run(parsed) = cli.command else { panic!("expected the run command") };
+        assert_eq!(config_hash(&parsed), config_hash(&args));
+        assert_eq!(parsed.trials, 3);
+        assert!(parsed.timeline && parsed.skip_build && parsed.format == OutputFormat::Both);
+    }
+}
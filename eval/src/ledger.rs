@@ -0,0 +1,152 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::{EvalError, Result},
+    EvalArgs,
+};
+
+/// A trial of a sweep that ran, as recorded in the ledger.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LedgerEntry {
+    /// The hash of the configuration the trial ran with, see [`config_hash`].
+    pub config: String,
+    /// The index of the measured trial of the configuration.
+    pub trial: usize,
+    /// The status of the run, `ok` or the status of its error.
+    pub status: String,
+    /// The error of a failed run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The trials a sweep has run, appended to a JSONL file next to its result files.
+///
+/// A configuration's trials run in order, so the ledger knows how far each configuration got from
+/// its successful entries and how often the next trial failed.
+pub struct Ledger {
+    /// The path of the ledger file.
+    path: PathBuf,
+    /// The entries of the ledger, in the order they were recorded.
+    entries: Vec<LedgerEntry>,
+}
+
+/// What remains of a configuration according to the ledger.
+#[derive(Debug, PartialEq)]
+pub struct Progress {
+    /// The number of measured trials that succeeded.
+    pub completed: usize,
+    /// The number of times the next trial failed.
+    pub failures: usize,
+}
+
+/// Returns the hash of the configuration of an evaluation.
+///
+/// It covers everything that changes what is measured, but not the number of trials or where the
+/// results are written, so a sweep can be resumed with more trials.
+pub fn config_hash(args: &EvalArgs) -> String {
+    let inputs: Vec<String> = args.inputs.iter().map(|path| path.display().to_string()).collect();
+    let mut description = format!(
+        "program={} prover={} hashfn={} shard_size={} block={:?} fixture={:?} inputs={:?} \
+         warmup={} mode={} prover_mode={} tamper_check={}",
+        args.program,
+        args.prover.to_string(),
+        args.hashfn.to_string(),
+        args.shard_size,
+        args.block_number,
+        args.fixture,
+        inputs,
+        args.warmup,
+        args.mode.to_string(),
+        args.prover_mode.to_string(),
+        args.tamper_check,
+    );
+    // Timing the spans of the prover slows it down, so timelines are part of the configuration,
+    // but only when recorded, to keep the hashes of the ledgers written before they could be.
+    if args.timeline {
+        description += " timeline=true";
+    }
+    hex::encode(&Sha256::digest(description.as_bytes())[..8])
+}
+
+impl Ledger {
+    /// The path of the ledger of the result files named `stem` in the directory.
+    pub fn path(dir: &Path, stem: &str) -> PathBuf {
+        dir.join(format!("{}.ledger.jsonl", stem))
+    }
+
+    /// Reads the ledger at the path, which is empty if it doesn't exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        let read_error = |e: String| {
+            EvalError::Config(format!("failed to read ledger {}: {}", path.display(), e))
+        };
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(read_error(e.to_string())),
+        };
+        let entries = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(|e| read_error(e.to_string())))
+            .collect::<Result<_>>()?;
+        Ok(Self { path: path.to_path_buf(), entries })
+    }
+
+    /// Returns how far the configuration got.
+    pub fn progress(&self, config: &str) -> Progress {
+        let entries: Vec<&LedgerEntry> =
+            self.entries.iter().filter(|entry| entry.config == config).collect();
+        let mut completed = 0;
+        while entries.iter().any(|e| e.trial == completed && e.status == "ok") {
+            completed += 1;
+        }
+        let failures = entries.iter().filter(|e| e.trial == completed && e.status != "ok").count();
+        Progress { completed, failures }
+    }
+
+    /// Appends an entry to the ledger file.
+    pub fn record(&mut self, entry: LedgerEntry) -> Result<()> {
+        let write_error = |e: std::io::Error| {
+            EvalError::Config(format!("failed to write ledger {}: {}", self.path.display(), e))
+        };
+        let mut file =
+            OpenOptions::new().create(true).append(true).open(&self.path).map_err(write_error)?;
+        writeln!(file, "{}", serde_json::to_string(&entry).unwrap()).map_err(write_error)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(config: &str, trial: usize, status: &str) -> LedgerEntry {
+        LedgerEntry { config: config.to_string(), trial, status: status.to_string(), error: None }
+    }
+
+    #[test]
+    fn progress_survives_a_restart() {
+        let path = std::env::temp_dir().join(format!("eval-{}-ledger.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut ledger = Ledger::load(&path).unwrap();
+        assert_eq!(ledger.progress("a"), Progress { completed: 0, failures: 0 });
+        ledger.record(entry("a", 0, "ok")).unwrap();
+        ledger.record(entry("a", 1, "proving_error")).unwrap();
+        ledger.record(entry("a", 1, "proving_error")).unwrap();
+        ledger.record(entry("b", 0, "ok")).unwrap();
+
+        let ledger = Ledger::load(&path).unwrap();
+        assert_eq!(ledger.progress("a"), Progress { completed: 1, failures: 2 });
+        assert_eq!(ledger.progress("b"), Progress { completed: 1, failures: 0 });
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,24 +1,77 @@
+mod blocks;
+mod breakdown;
+mod compare;
+mod environment;
+mod error;
+mod evaluator;
+mod fixtures;
+mod guest;
+mod harness;
+mod inputs;
+mod isolation;
+mod ledger;
+mod monitor;
+mod pricing;
+mod programs;
+mod proof_size;
+mod report;
+mod results;
+#[cfg(feature = "risc0")]
 mod risc0;
 mod sp1;
+mod stats;
+mod sweep;
+mod tamper;
+mod timeline;
 mod types;
 mod utils;
 
 use std::{
-    fs::{create_dir_all, OpenOptions},
-    path::PathBuf,
+    fs::create_dir_all,
+    path::{Path, PathBuf},
 };
 
-use clap::{command, Parser};
-use csv::WriterBuilder;
-use serde::Serialize;
+use blocks::BlocksArgs;
+use clap::{Args, Parser, Subcommand};
+use compare::CompareArgs;
+use error::EvalError;
+use evaluator::Registry;
+use guest::BuildArgs;
+use programs::{ProgramRegistry, REGISTRY_PATH};
+use report::ReportArgs;
+use sweep::SweepArgs;
 use types::*;
 
-/// The argument passed through the CLI.
-#[derive(Parser, Clone)]
-#[command(about = "Evaluate the performance of a zkVM on a program.")]
+/// The command line interface of the evaluation binary.
+#[derive(Parser)]
+#[command(about = "Evaluate the performance of zkVMs on programs.")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Evaluate the performance of a zkVM on a program.
+    Run(EvalArgs),
+    /// Evaluate every combination of a matrix of programs, provers and options.
+    Sweep(SweepArgs),
+    /// Build guest programs, skipping the ones whose sources haven't changed.
+    Build(BuildArgs),
+    /// Index the block inputs and write their manifest.
+    Blocks(BlocksArgs),
+    /// Compare two result files and fail on regressions.
+    Compare(CompareArgs),
+    /// Write a Markdown summary and an HTML page of charts from result files.
+    Report(ReportArgs),
+}
+
+/// The arguments of a single evaluation.
+#[derive(Args, Clone)]
 pub struct EvalArgs {
+    /// The program to evaluate, as named in the program registry.
     #[arg(long)]
-    program: ProgramId,
+    program: String,
     #[arg(long)]
     prover: ProverId,
     #[arg(long)]
@@ -29,108 +82,70 @@ pub struct EvalArgs {
     filename: String,
     #[arg(long)]
     block_number: Option<u64>,
-}
-
-/// The performance report of a zkVM on a program.
-#[derive(Debug, Serialize, Default)]
-pub struct PerformanceReport {
-    /// The program that is being evaluated.
-    pub program: String,
-    /// The prover that is being evaluated.
-    pub prover: String,
-    /// The hash function that is being evaluated.
-    pub hashfn: String,
-    /// The shard size that is being evaluated.
-    pub shard_size: u64,
-    /// The number of shards.
-    pub shards: usize,
-    /// The reported number of cycles.
-    ///
-    /// Note that this number may vary based on the zkVM.
-    pub cycles: u64,
-    /// The reported speed in cycles per second.
-    pub speed: f64,
-    /// The reported duration of the execution in seconds.
-    pub execution_duration: f64,
-    /// The reported duration of the prover in seconds.
-    pub prove_duration: f64,
-    /// The reported duration of the core proving time in seconds.
-    pub core_prove_duration: f64,
-    /// The reported duration of the verifier in seconds.
-    pub core_verify_duration: f64,
-    /// The size of the core proof.
-    pub core_proof_size: usize,
-    /// The reported duration of the recursive proving time in seconds.
-    pub compress_prove_duration: f64,
-    /// The reported duration of the verifier in seconds.
-    pub compress_verify_duration: f64,
-    /// The size of the recursive proof in bytes.
-    pub compress_proof_size: usize,
+    /// The fixture the Fuel programs are evaluated on.
+    #[arg(long)]
+    fixture: Option<String>,
+    /// An input file written to the guest after the program's own inputs, repeatable. Read as
+    /// JSON, bincode (`.bin`) or raw bytes depending on its extension.
+    #[arg(long = "input")]
+    inputs: Vec<PathBuf>,
+    /// The number of measured trials, all sharing the proving key from a single setup.
+    #[arg(long, default_value_t = 1)]
+    trials: usize,
+    /// The number of trials run before the measured ones and left out of the statistics.
+    #[arg(long, default_value_t = 0)]
+    warmup: usize,
+    /// How far through the proving pipeline to run.
+    #[arg(long, value_enum, default_value_t = Mode::Full)]
+    mode: Mode,
+    /// Whether to generate real proofs, or mock ones that only check the pipeline.
+    #[arg(long, value_enum, default_value_t = ProverMode::Real)]
+    prover_mode: ProverMode,
+    /// Check that the verifiers reject tampered copies of the proofs in the last trial.
+    #[arg(long)]
+    tamper_check: bool,
+    /// Record the timeline of the core proofs from the spans of the prover, which slows it down.
+    #[arg(long)]
+    timeline: bool,
+    /// Use the ELF of the last build instead of building the program when it changed.
+    #[arg(long)]
+    skip_build: bool,
+    /// The format of the result files.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Csv)]
+    format: OutputFormat,
 }
 
 fn main() {
-    let args = EvalArgs::parse();
+    let cli = Cli::parse();
+    timeline::init_logger();
 
-    // Select the correct implementation based on the prover.
-    let report = match args.prover {
-        ProverId::Risc0 => risc0::Risc0Evaluator::eval(&args),
-        ProverId::SP1 => sp1::SP1Evaluator::eval(&args),
-    };
+    // Only the commands that evaluate or build programs read the program registry.
+    let registry = Registry::default();
+    let programs = || ProgramRegistry::load(Path::new(REGISTRY_PATH));
 
     // Create the results directory if it doesn't exist.
     let results_dir = PathBuf::from("benchmarks");
-    create_dir_all(&results_dir).unwrap();
-
-    // Create the file.
-    let filename = format!("{}_{}.csv", args.filename, env!("VERGEN_GIT_SHA"));
-    let path = results_dir.join(filename);
-    let file = OpenOptions::new().create(true).append(true).open(path.clone()).unwrap();
+    let result = create_dir_all(&results_dir).map_err(|e| {
+        EvalError::Config(format!(
+            "failed to create the results directory {}: {}",
+            results_dir.display(),
+            e
+        ))
+    });
+    let result = result.and_then(|()| match cli.command {
+        Command::Run(args) => programs()
+            .and_then(|programs| harness::evaluate(&args, &registry, &programs, &results_dir)),
+        Command::Sweep(args) => {
+            programs().and_then(|programs| sweep::run(&args, &registry, &programs, &results_dir))
+        }
+        Command::Build(args) => programs().and_then(|programs| guest::run(&args, &programs)),
+        Command::Blocks(args) => blocks::run(&args),
+        Command::Compare(args) => compare::run(&args),
+        Command::Report(args) => report::run(&args),
+    });
 
-    // Write the row and the header, if needed.
-    let mut writer = WriterBuilder::new().from_writer(&file);
-    if file.metadata().unwrap().len() == 0 {
-        writer
-            .write_record(&[
-                "program",
-                "prover",
-                "hashfn",
-                "shard_size",
-                "shards",
-                "cycles",
-                "speed",
-                "execution_duration",
-                "prove_duration",
-                "core_prove_duration",
-                "core_verify_duration",
-                "core_proof_size",
-                "compress_prove_duration",
-                "compress_verify_duration",
-                "compress_proof_size",
-            ])
-            .unwrap();
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        std::process::exit(err.exit_code());
     }
-    writer
-        .serialize(&[
-            report.program,
-            report.prover,
-            report.hashfn,
-            report.shard_size.to_string(),
-            report.shards.to_string(),
-            report.cycles.to_string(),
-            report.speed.to_string(),
-            report.execution_duration.to_string(),
-            report.prove_duration.to_string(),
-            report.core_prove_duration.to_string(),
-            report.core_verify_duration.to_string(),
-            report.core_proof_size.to_string(),
-            report.compress_prove_duration.to_string(),
-            report.compress_verify_duration.to_string(),
-            report.compress_proof_size.to_string(),
-        ])
-        .unwrap();
-    writer.flush().unwrap();
-
-    let latest_filename = "benchmarks_latest.csv";
-    let latest_path = results_dir.join(latest_filename);
-    std::fs::copy(&path, &latest_path).unwrap();
 }
@@ -0,0 +1,82 @@
+use std::{
+    fs,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::utils::time_operation;
+
+/// How often the memory usage is sampled while an operation runs.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The resources used by the process while an operation ran.
+///
+/// The values are read from `/proc/self`, so they are all zero on platforms without procfs.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ResourceUsage {
+    /// The peak resident set size in bytes.
+    pub peak_rss: u64,
+    /// The user and system CPU time in seconds.
+    pub cpu_time: f64,
+    /// The average number of CPU cores used, i.e. the CPU time over the wall-clock time.
+    pub cpu_cores: f64,
+}
+
+/// Times the operation while sampling the memory and CPU usage of the process in the background.
+pub fn measure_operation<T, F: FnOnce() -> T>(operation: F) -> (T, Duration, ResourceUsage) {
+    let stop = Arc::new(AtomicBool::new(false));
+    let sampler = {
+        let stop = stop.clone();
+        thread::spawn(move || {
+            let mut peak_rss = current_rss().unwrap_or(0);
+            while !stop.load(Ordering::Relaxed) {
+                thread::sleep(SAMPLE_INTERVAL);
+                peak_rss = peak_rss.max(current_rss().unwrap_or(0));
+            }
+            peak_rss
+        })
+    };
+
+    let cpu_start = cpu_time();
+    let (result, duration) = time_operation(operation);
+    let cpu_end = cpu_time();
+
+    stop.store(true, Ordering::Relaxed);
+    let peak_rss = sampler.join().unwrap_or(0).max(current_rss().unwrap_or(0));
+
+    let cpu_time = match (cpu_start, cpu_end) {
+        (Some(start), Some(end)) => end - start,
+        _ => 0.0,
+    };
+    let cpu_cores = if duration.is_zero() { 0.0 } else { cpu_time / duration.as_secs_f64() };
+
+    (result, duration, ResourceUsage { peak_rss, cpu_time, cpu_cores })
+}
+
+/// Reads the current resident set size of the process in bytes from `/proc/self/status`.
+fn current_rss() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes = line.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// Reads the user and system CPU time of the process in seconds from `/proc/self/stat`.
+fn cpu_time() -> Option<f64> {
+    let stat = fs::read_to_string("/proc/self/stat").ok()?;
+    // The command name may contain spaces, so the fields are counted from the closing parenthesis,
+    // after which `utime` and `stime` are the 12th and 13th fields.
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime = fields.get(11)?.parse::<u64>().ok()?;
+    let stime = fields.get(12)?.parse::<u64>().ok()?;
+
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks_per_second <= 0 {
+        return None;
+    }
+    Some((utime + stime) as f64 / ticks_per_second as f64)
+}
@@ -0,0 +1,131 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use serde::Deserialize;
+
+use crate::{
+    error::{EvalError, Result},
+    results::Row,
+};
+
+/// The number of bytes in a GiB, the unit memory is priced in.
+const GIB: f64 = (1u64 << 30) as f64;
+
+/// The columns a priced row gets, in dollars.
+const COST_COLUMNS: [&str; 3] =
+    ["cost_per_proof", "cost_per_million_cycles", "cost_per_million_gas"];
+
+/// The price of a machine.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct MachinePrice {
+    /// The price of the machine in dollars per hour.
+    pub hourly: f64,
+    /// The price of memory in dollars per GiB-hour, charged on the peak memory of proving.
+    #[serde(default)]
+    pub memory_hourly: Option<f64>,
+}
+
+/// The prices of the machines results are measured on, read from a TOML file.
+///
+/// Machines are labeled by instance type, or by CPU model for machines without one.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Pricing {
+    /// The prices by machine label.
+    #[serde(default)]
+    pub machines: BTreeMap<String, MachinePrice>,
+}
+
+impl Pricing {
+    /// Reads the pricing table at the path.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            EvalError::Config(format!("failed to read pricing {}: {}", path.display(), e))
+        })?;
+        toml::from_str(&contents)
+            .map_err(|e| EvalError::Config(format!("invalid pricing {}: {}", path.display(), e)))
+    }
+
+    /// Returns the price of the machine a row was measured on, if it is priced.
+    fn price(&self, row: &Row) -> Option<&MachinePrice> {
+        let label = ["instance_type", "cpu_model"]
+            .iter()
+            .find_map(|column| row.get(*column).filter(|label| !label.is_empty()))?;
+        self.machines.get(label)
+    }
+
+    /// Returns the cost of a proof of the row in dollars, if its machine is priced and it proved.
+    ///
+    /// The proof is charged the proving time, core and compression, at the hourly price of the
+    /// machine and of its peak memory while proving.
+    pub fn cost_per_proof(&self, row: &Row) -> Option<f64> {
+        let price = self.price(row)?;
+        let column = |name: &str| row.get(name).and_then(|value| value.parse::<f64>().ok());
+        let hours = column("prove_duration_median").filter(|duration| *duration > 0.0)? / 3600.0;
+        let peak_memory = ["core_prove_peak_rss", "compress_prove_peak_rss"]
+            .iter()
+            .filter_map(|name| column(name))
+            .fold(0.0, f64::max);
+        Some(hours * (price.hourly + price.memory_hourly.unwrap_or(0.0) * peak_memory / GIB))
+    }
+
+    /// Adds the cost columns to the rows of priced machines: the cost per proof, per million
+    /// cycles and, for rows that record the gas used by their block, per million gas.
+    pub fn annotate(&self, rows: &mut [Row]) {
+        for row in rows {
+            let Some(cost) = self.cost_per_proof(row) else { continue };
+            let per_million = |column: &str| {
+                let amount = row.get(column)?.parse::<f64>().ok().filter(|amount| *amount > 0.0)?;
+                Some(cost / amount * 1e6)
+            };
+            let costs = [Some(cost), per_million("cycles"), per_million("gas_used")];
+            for (column, cost) in COST_COLUMNS.iter().zip(costs) {
+                if let Some(cost) = cost {
+                    row.insert(column.to_string(), cost.to_string());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proofs_are_charged_for_time_and_memory() {
+        let pricing: Pricing = toml::from_str(
+            r#"
+            [machines]
+            "r7i.16xlarge" = { hourly = 4.0 }
+            "AMD EPYC" = { hourly = 1.0, memory_hourly = 0.01 }
+            "#,
+        )
+        .unwrap();
+        let row = |instance: &str, gas_used: &str| -> Row {
+            [
+                ("instance_type", instance),
+                ("cpu_model", "AMD EPYC"),
+                ("prove_duration_median", "1800"),
+                ("cycles", "2000000"),
+                ("gas_used", gas_used),
+                ("core_prove_peak_rss", "107374182400"),
+                ("compress_prove_peak_rss", "53687091200"),
+            ]
+            .into_iter()
+            .map(|(column, value)| (column.to_string(), value.to_string()))
+            .collect()
+        };
+        let mut rows = vec![row("r7i.16xlarge", "4000000"), row("", ""), row("g6.16xlarge", "")];
+        pricing.annotate(&mut rows);
+
+        let cost = |row: &Row, column: &str| row.get(column).map(|v| v.parse::<f64>().unwrap());
+        assert_eq!(cost(&rows[0], "cost_per_proof"), Some(2.0));
+        assert_eq!(cost(&rows[0], "cost_per_million_cycles"), Some(1.0));
+        assert_eq!(cost(&rows[0], "cost_per_million_gas"), Some(0.5));
+        // Half an hour of the machine, and of its 100 GiB peak memory.
+        assert_eq!(cost(&rows[1], "cost_per_proof"), Some(1.0));
+        assert_eq!(cost(&rows[1], "cost_per_million_gas"), None);
+        assert_eq!(cost(&rows[2], "cost_per_proof"), None);
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{EvalError, Result},
+    ProverId,
+};
+
+/// The path of the program registry, relative to the root of the repository.
+pub const REGISTRY_PATH: &str = "programs/programs.toml";
+
+/// The provider of the inputs a program reads.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum InputProvider {
+    /// The program reads no input.
+    #[default]
+    None,
+    /// The program reads the block selected with `--block-number`.
+    Block,
+    /// The program reads the parameters of a SHA-256 hash chain.
+    Sha2Chain(Sha2ChainInput),
+    /// The program reads the raw input of the Fuel fixture selected with `--fixture`.
+    Fixture(FixtureSet),
+}
+
+/// The set of Fuel test fixtures a program reads its input from.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FixtureSet {
+    /// The blocks executed by the block execution game.
+    BlockExecution,
+    /// The compressed blocks of the decompression game.
+    Decompression,
+}
+
+/// The parameters of the sha2-chain program, in the order the guest reads them.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Sha2ChainInput {
+    /// The number of hashes in the chain.
+    pub iterations: u32,
+    /// The size in bytes of each preimage.
+    pub preimage_size: u32,
+    /// Whether the guest hashes with the SHA-256 precompile of the prover or in software.
+    pub precompile: bool,
+}
+
+impl Sha2ChainInput {
+    /// The number of bytes hashed by the chain.
+    pub fn bytes_hashed(&self) -> u64 {
+        self.iterations as u64 * self.preimage_size as u64
+    }
+}
+
+/// The guest crate of a program for a prover.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GuestSpec {
+    /// The cargo project of the guest, relative to the directory of the registry.
+    pub dir: PathBuf,
+    /// The package to build, if the project is a workspace with several of them.
+    pub package: Option<String>,
+    /// The features the guest is built with.
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// The path of the built ELF, relative to the project.
+    pub elf: PathBuf,
+    /// The environment variables that configure the prover while it runs the program.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// The version of the zkVM the guest is built against, if it is pinned apart from the one the
+    /// eval binary proves with.
+    pub zkvm_version: Option<String>,
+}
+
+/// A program of the registry.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ProgramSpec {
+    /// The provider of the program's inputs.
+    #[serde(default)]
+    pub input: InputProvider,
+    /// The guest for SP1, if SP1 is supported.
+    pub sp1: Option<GuestSpec>,
+    /// The guest for RISC0, if RISC0 is supported.
+    pub risc0: Option<GuestSpec>,
+}
+
+/// The programs that can be evaluated, keyed by name.
+pub struct ProgramRegistry {
+    /// The directory the guest projects are relative to.
+    root: PathBuf,
+    programs: BTreeMap<String, ProgramSpec>,
+}
+
+impl ProgramSpec {
+    /// Returns the guest of the program for the prover, if the prover is supported.
+    pub fn guest(&self, prover: ProverId) -> Option<&GuestSpec> {
+        match prover {
+            ProverId::SP1 => self.sp1.as_ref(),
+            ProverId::Risc0 => self.risc0.as_ref(),
+        }
+    }
+}
+
+impl ProgramRegistry {
+    /// Reads the registry at the path.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            EvalError::Config(format!("failed to read program registry {}: {}", path.display(), e))
+        })?;
+        let root = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        Self::parse(root, &contents).map_err(|e| {
+            EvalError::Config(format!("invalid program registry {}: {}", path.display(), e))
+        })
+    }
+
+    /// Parses a registry whose guest projects are relative to the root.
+    pub fn parse(root: PathBuf, contents: &str) -> std::result::Result<Self, String> {
+        let programs: BTreeMap<String, ProgramSpec> =
+            toml::from_str(contents).map_err(|e| e.to_string())?;
+        if let Some(name) = programs.iter().find(|(_, p)| p.sp1.is_none() && p.risc0.is_none()) {
+            return Err(format!("program {} has no guest for any prover", name.0));
+        }
+        Ok(Self { root, programs })
+    }
+
+    /// Returns the program with the name.
+    pub fn get(&self, name: &str) -> Result<&ProgramSpec> {
+        self.programs.get(name).ok_or_else(|| {
+            EvalError::Config(format!(
+                "Program {} is not registered. Please choose from: {}",
+                name,
+                self.programs.keys().cloned().collect::<Vec<_>>().join(", ")
+            ))
+        })
+    }
+
+    /// Returns the guest of the program for the prover.
+    pub fn guest(&self, name: &str, prover: ProverId) -> Result<&GuestSpec> {
+        self.get(name)?.guest(prover).ok_or_else(|| {
+            EvalError::Config(format!(
+                "Program {} doesn't support the {} prover",
+                name,
+                prover.to_string()
+            ))
+        })
+    }
+
+    /// The directory the guest projects are relative to.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_in_repository_is_valid() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join(REGISTRY_PATH);
+        let registry = ProgramRegistry::load(&path).unwrap();
+        let reth = registry.get("reth").unwrap();
+        assert_eq!(reth.input, InputProvider::Block);
+        assert!(registry
+            .guest("reth", ProverId::SP1)
+            .unwrap()
+            .env
+            .contains_key("SHARD_CHUNKING_MULTIPLIER"));
+        let software = registry.get("sha2-chain-software").unwrap();
+        assert!(matches!(
+            software.input,
+            InputProvider::Sha2Chain(Sha2ChainInput { precompile: false, .. })
+        ));
+        let decompression = registry.get("fuel-decompression").unwrap();
+        assert_eq!(decompression.input, InputProvider::Fixture(FixtureSet::Decompression));
+        assert_eq!(decompression.risc0.as_ref().unwrap().zkvm_version.as_deref(), Some("1.2.5"));
+        assert!(registry.get("unknown").is_err());
+    }
+
+    #[test]
+    fn programs_without_guests_are_rejected() {
+        assert!(ProgramRegistry::parse(PathBuf::new(), "[empty]\ninput = \"none\"").is_err());
+        assert!(ProgramRegistry::parse(PathBuf::new(), "[typo]\nsp2 = {}").is_err());
+    }
+}
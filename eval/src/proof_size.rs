@@ -0,0 +1,49 @@
+use serde::Serialize;
+
+/// The size of a proof, split the same way for every prover so that sizes can be compared.
+///
+/// Everything is measured in bytes, and whatever is serialized is serialized with bincode.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ProofSize {
+    /// The cryptographic proof alone, serialized: the shard proofs of SP1, or the seals of the
+    /// segments or succinct receipt of RISC0.
+    pub seal: usize,
+    /// What a verifier needs: the proof together with its public values, serialized.
+    pub receipt: usize,
+    /// The public values committed by the guest: the public values of SP1, or the RISC0 journal.
+    pub public_values: usize,
+}
+
+/// Returns the size of a value serialized with bincode.
+pub fn serialized_size<T: Serialize + ?Sized>(value: &T) -> usize {
+    bincode::serialized_size(value).expect("proofs can be serialized") as usize
+}
+
+impl ProofSize {
+    /// Measures a proof that is verified together with public values kept apart from it, like the
+    /// proofs of SP1. The proof is serialized as the seal, and with the public values as the
+    /// receipt.
+    pub fn with_public_values<P: Serialize + ?Sized>(proof: &P, public_values: &[u8]) -> Self {
+        Self {
+            seal: serialized_size(proof),
+            receipt: serialized_size(&(proof, public_values)),
+            public_values: public_values.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn receipts_hold_the_seal_and_the_public_values() {
+        let proof = vec![7u32; 16];
+        let size = ProofSize::with_public_values(&proof, &[1, 2, 3]);
+
+        // bincode prefixes sequences with their length as a u64.
+        assert_eq!(size.seal, 8 + 16 * 4);
+        assert_eq!(size.public_values, 3);
+        assert_eq!(size.receipt, size.seal + 8 + size.public_values);
+    }
+}
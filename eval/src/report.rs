@@ -0,0 +1,488 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    fs,
+    path::PathBuf,
+};
+
+use crate::{
+    error::{EvalError, Result},
+    pricing::Pricing,
+    results::{read_rows, Row},
+};
+
+/// The size of a chart in pixels.
+const CHART_WIDTH: f64 = 640.0;
+const CHART_HEIGHT: f64 = 320.0;
+
+/// The margins around the plot area of a chart, for the axes and legend.
+const MARGIN_LEFT: f64 = 80.0;
+const MARGIN_RIGHT: f64 = 160.0;
+const MARGIN_TOP: f64 = 30.0;
+const MARGIN_BOTTOM: f64 = 40.0;
+
+/// The colors of the series of a chart, in order.
+const COLORS: [&str; 6] = ["#4e79a7", "#f28e2b", "#59a14f", "#e15759", "#76b7b2", "#b07aa1"];
+
+/// The grouped bar charts drawn for every machine, by column, title and unit.
+const BAR_CHARTS: [(&str, &str, &str); 4] = [
+    ("core_prove_duration_median", "Core proving time", "s"),
+    ("speed", "Proving speed", "cycles/s"),
+    ("core_proof_size", "Core proof size", "bytes"),
+    ("compress_proof_size", "Compressed proof size", "bytes"),
+];
+
+/// The bar charts drawn for the machines that are priced, by column, title and unit.
+const COST_CHARTS: [(&str, &str, &str); 2] = [
+    ("cost_per_proof", "Cost per proof", "$"),
+    ("cost_per_million_cycles", "Cost per million cycles", "$"),
+];
+
+/// The arguments of the report command.
+#[derive(clap::Args, Clone)]
+pub struct ReportArgs {
+    /// The result files to report on, in CSV or JSONL.
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// The path of the report, without extension. A `.md` and a `.html` file are written.
+    #[arg(long, default_value = "benchmarks/report")]
+    output: PathBuf,
+    /// A TOML table of machine prices, to estimate the cost of the proofs.
+    #[arg(long)]
+    pricing: Option<PathBuf>,
+}
+
+/// Returns the machine a row was produced on, which the report is grouped by.
+fn machine(row: &Row) -> String {
+    let column = |name: &str| row.get(name).cloned().unwrap_or_default();
+    match (column("instance_type"), column("cpu_model")) {
+        (instance, cpu) if instance.is_empty() && cpu.is_empty() => "unknown machine".to_string(),
+        (instance, cpu) if instance.is_empty() => cpu,
+        (instance, cpu) if cpu.is_empty() => instance,
+        (instance, cpu) => format!("{} ({})", instance, cpu),
+    }
+}
+
+/// Returns the value of a numeric column, or zero if the row doesn't have one.
+fn value(row: &Row, column: &str) -> f64 {
+    row.get(column).and_then(|value| value.parse().ok()).unwrap_or(0.0)
+}
+
+/// Returns the value of a column, or an empty string if the row doesn't have it.
+fn text<'a>(row: &'a Row, column: &str) -> &'a str {
+    row.get(column).map(String::as_str).unwrap_or("")
+}
+
+/// Returns the mean of the column by category and series, over the rows that measured it.
+fn means(
+    rows: &[&Row],
+    column: &str,
+    category: impl Fn(&Row) -> String,
+    series: impl Fn(&Row) -> String,
+) -> BTreeMap<String, BTreeMap<String, f64>> {
+    let mut sums: BTreeMap<String, BTreeMap<String, (f64, usize)>> = BTreeMap::new();
+    for row in rows {
+        let value = value(row, column);
+        if value > 0.0 {
+            let sum = sums.entry(category(row)).or_default().entry(series(row)).or_default();
+            sum.0 += value;
+            sum.1 += 1;
+        }
+    }
+    sums.into_iter()
+        .map(|(category, series)| {
+            (category, series.into_iter().map(|(s, (sum, n))| (s, sum / n as f64)).collect())
+        })
+        .collect()
+}
+
+/// Returns a function labeling the program of a row, with the start of its input digest if the
+/// rows measured the program on more than one input, so that different inputs aren't averaged.
+fn program_label<'a>(rows: &[&'a Row]) -> impl Fn(&Row) -> String + 'a {
+    let mut inputs: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for row in rows {
+        inputs.entry(text(row, "program")).or_default().insert(text(row, "input_digest"));
+    }
+    move |row| {
+        let program = text(row, "program");
+        match inputs.get(program) {
+            Some(digests) if digests.len() > 1 => {
+                let digest = text(row, "input_digest");
+                format!("{} ({})", program, &digest[..digest.len().min(8)])
+            }
+            _ => program.to_string(),
+        }
+    }
+}
+
+/// Returns the prover and hash function of a row.
+fn prover_label(row: &Row) -> String {
+    format!("{} {}", text(row, "prover"), text(row, "hashfn"))
+}
+
+/// Returns the prover, hash function and log2 shard size of a row, which together with the
+/// program and its input make up the configuration that is measured.
+fn configuration_label(row: &Row) -> String {
+    format!("{} 2^{}", prover_label(row), text(row, "shard_size"))
+}
+
+/// Formats a cost in dollars, or a dash if it is unknown.
+fn dollars(row: &Row, column: &str) -> String {
+    match row.get(column).and_then(|value| value.parse::<f64>().ok()) {
+        Some(cost) => format!("${:.4}", cost),
+        None => "-".to_string(),
+    }
+}
+
+/// Escapes text for HTML and SVG.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Formats a value with an SI suffix, e.g. `1.5M`.
+fn short(value: f64) -> String {
+    let (scaled, suffix) = match value.abs() {
+        v if v >= 1e9 => (value / 1e9, "G"),
+        v if v >= 1e6 => (value / 1e6, "M"),
+        v if v >= 1e3 => (value / 1e3, "k"),
+        _ => (value, ""),
+    };
+    format!("{:.3}", scaled).trim_end_matches('0').trim_end_matches('.').to_string() + suffix
+}
+
+/// Opens an SVG chart with its title, y axis and legend.
+fn chart_start(svg: &mut String, title: &str, unit: &str, max: f64, series: &[&String]) {
+    let bottom = CHART_HEIGHT - MARGIN_BOTTOM;
+    let _ = write!(
+        svg,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" font-family="sans-serif" font-size="11"><text x="{x}" y="18" font-size="14" font-weight="bold">{title} ({unit})</text><line x1="{l}" y1="{t}" x2="{l}" y2="{b}" stroke="#333"/><line x1="{l}" y1="{b}" x2="{r}" y2="{b}" stroke="#333"/>"##,
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT,
+        x = MARGIN_LEFT,
+        title = escape(title),
+        unit = escape(unit),
+        l = MARGIN_LEFT,
+        t = MARGIN_TOP,
+        b = bottom,
+        r = CHART_WIDTH - MARGIN_RIGHT,
+    );
+    for tick in 0..=4 {
+        let y = bottom - (bottom - MARGIN_TOP) * tick as f64 / 4.0;
+        let _ = write!(
+            svg,
+            r##"<text x="{x}" y="{y}" text-anchor="end">{label}</text><line x1="{l}" y1="{y}" x2="{r}" y2="{y}" stroke="#ddd"/>"##,
+            x = MARGIN_LEFT - 6.0,
+            y = y + 4.0,
+            label = short(max * tick as f64 / 4.0),
+            l = MARGIN_LEFT,
+            r = CHART_WIDTH - MARGIN_RIGHT,
+        );
+    }
+    for (index, name) in series.iter().enumerate() {
+        let y = MARGIN_TOP + 16.0 * index as f64;
+        let _ = write!(
+            svg,
+            r#"<rect x="{x}" y="{y}" width="10" height="10" fill="{color}"/><text x="{tx}" y="{ty}">{name}</text>"#,
+            x = CHART_WIDTH - MARGIN_RIGHT + 10.0,
+            color = COLORS[index % COLORS.len()],
+            tx = CHART_WIDTH - MARGIN_RIGHT + 24.0,
+            ty = y + 9.0,
+            name = escape(name),
+        );
+    }
+}
+
+/// Draws a bar chart of the values by category, with a bar per series in each category.
+fn bar_chart(title: &str, unit: &str, data: &BTreeMap<String, BTreeMap<String, f64>>) -> String {
+    let series: Vec<&String> = {
+        let mut series: Vec<&String> = data.values().flat_map(|s| s.keys()).collect();
+        series.sort();
+        series.dedup();
+        series
+    };
+    let max = data.values().flat_map(|s| s.values()).fold(0.0, |a: f64, &b| a.max(b));
+
+    let mut svg = String::new();
+    chart_start(&mut svg, title, unit, max, &series);
+    let bottom = CHART_HEIGHT - MARGIN_BOTTOM;
+    let group_width = (CHART_WIDTH - MARGIN_LEFT - MARGIN_RIGHT) / data.len().max(1) as f64;
+    let bar_width = group_width * 0.8 / series.len().max(1) as f64;
+    for (group, (category, values)) in data.iter().enumerate() {
+        let left = MARGIN_LEFT + group_width * (group as f64 + 0.1);
+        for (index, name) in series.iter().enumerate() {
+            let Some(&value) = values.get(*name) else { continue };
+            let height = if max > 0.0 { (bottom - MARGIN_TOP) * value / max } else { 0.0 };
+            let _ = write!(
+                svg,
+                r#"<rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" fill="{color}"><title>{name}: {value}</title></rect>"#,
+                x = left + bar_width * index as f64,
+                y = bottom - height,
+                w = bar_width,
+                h = height,
+                color = COLORS[index % COLORS.len()],
+                name = escape(name),
+                value = short(value),
+            );
+        }
+        let _ = write!(
+            svg,
+            r#"<text x="{x:.1}" y="{y}" text-anchor="middle">{category}</text>"#,
+            x = left + group_width * 0.4,
+            y = bottom + 16.0,
+            category = escape(category),
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Draws a line chart of the values by x value, with a line per series.
+fn line_chart(title: &str, unit: &str, data: &BTreeMap<String, BTreeMap<u64, f64>>) -> String {
+    let series: Vec<&String> = data.keys().collect();
+    let xs: Vec<u64> = {
+        let mut xs: Vec<u64> = data.values().flat_map(|s| s.keys().copied()).collect();
+        xs.sort();
+        xs.dedup();
+        xs
+    };
+    let max = data.values().flat_map(|s| s.values()).fold(0.0, |a: f64, &b| a.max(b));
+
+    let mut svg = String::new();
+    chart_start(&mut svg, title, unit, max, &series);
+    let bottom = CHART_HEIGHT - MARGIN_BOTTOM;
+    let step = (CHART_WIDTH - MARGIN_LEFT - MARGIN_RIGHT) / xs.len().max(1) as f64;
+    let x_of =
+        |x: u64| MARGIN_LEFT + step * (xs.iter().position(|&v| v == x).unwrap() as f64 + 0.5);
+    let y_of =
+        |value: f64| if max > 0.0 { bottom - (bottom - MARGIN_TOP) * value / max } else { bottom };
+    for &x in &xs {
+        let _ = write!(
+            svg,
+            r#"<text x="{x:.1}" y="{y}" text-anchor="middle">{label}</text>"#,
+            x = x_of(x),
+            y = bottom + 16.0,
+            label = x,
+        );
+    }
+    for (index, (name, values)) in data.iter().enumerate() {
+        let color = COLORS[index % COLORS.len()];
+        let points: Vec<String> =
+            values.iter().map(|(&x, &v)| format!("{:.1},{:.1}", x_of(x), y_of(v))).collect();
+        let _ = write!(
+            svg,
+            r#"<polyline points="{points}" fill="none" stroke="{color}" stroke-width="2"/>"#,
+            points = points.join(" "),
+        );
+        for (&x, &v) in values {
+            let _ = write!(
+                svg,
+                r#"<circle cx="{cx:.1}" cy="{cy:.1}" r="3" fill="{color}"><title>{name} at {x}: {value}</title></circle>"#,
+                cx = x_of(x),
+                cy = y_of(v),
+                name = escape(name),
+                value = short(v),
+            );
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Returns the rows grouped by machine, keeping the rows of successful runs with real proofs.
+fn by_machine(rows: &[Row]) -> BTreeMap<String, Vec<&Row>> {
+    let mut machines: BTreeMap<String, Vec<&Row>> = BTreeMap::new();
+    let measured =
+        |row: &&Row| matches!(text(row, "status"), "ok" | "") && text(row, "prover_mode") != "mock";
+    for row in rows.iter().filter(measured) {
+        machines.entry(machine(row)).or_default().push(row);
+    }
+    machines
+}
+
+/// Renders the summary table of every machine in Markdown.
+fn markdown(rows: &[Row]) -> String {
+    let mut md = String::from("# Benchmark Report\n");
+    for (machine, rows) in by_machine(rows) {
+        let _ = write!(
+            md,
+            "\n## {}\n\n| program | prover | hashfn | shard size | cycles | core prove (s) | cycles/s | core proof (bytes) | compressed proof (bytes) |\n|---|---|---|---:|---:|---:|---:|---:|---:|\n",
+            machine
+        );
+        for row in &rows {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} | {} | {} | {:.3} | {} | {} | {} |",
+                text(row, "program"),
+                text(row, "prover"),
+                text(row, "hashfn"),
+                text(row, "shard_size"),
+                text(row, "cycles"),
+                value(row, "core_prove_duration_median"),
+                short(value(row, "speed")),
+                text(row, "core_proof_size"),
+                text(row, "compress_proof_size"),
+            );
+        }
+
+        // The cost of the proofs, if the machine is priced.
+        let priced: Vec<&&Row> =
+            rows.iter().filter(|row| row.contains_key("cost_per_proof")).collect();
+        if priced.is_empty() {
+            continue;
+        }
+        md.push_str("\n| program | prover | hashfn | shard size | $/proof | $/M cycles | $/M gas |\n|---|---|---|---:|---:|---:|---:|\n");
+        for row in priced {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} | {} | {} | {} | {} |",
+                text(row, "program"),
+                text(row, "prover"),
+                text(row, "hashfn"),
+                text(row, "shard_size"),
+                dollars(row, "cost_per_proof"),
+                dollars(row, "cost_per_million_cycles"),
+                dollars(row, "cost_per_million_gas"),
+            );
+        }
+    }
+    md
+}
+
+/// Renders the charts of every machine as a single HTML page, with no external resources.
+fn html(rows: &[Row]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Benchmark Report</title><style>body{font-family:sans-serif;margin:2em}svg{margin:1em 1em 0 0}</style></head><body><h1>Benchmark Report</h1>\n",
+    );
+    for (machine, rows) in by_machine(rows) {
+        let _ = writeln!(html, "<h2>{}</h2>", escape(&machine));
+        let program = program_label(&rows);
+        for (column, title, unit) in BAR_CHARTS.into_iter().chain(COST_CHARTS) {
+            let data = means(&rows, column, &program, configuration_label);
+            if !data.is_empty() {
+                html.push_str(&bar_chart(title, unit, &data));
+                html.push('\n');
+            }
+        }
+
+        // A shard size sweep for every program and input that was proven with more than one
+        // shard size, with a line for every prover and hash function.
+        let column = "core_prove_duration_median";
+        for label in means(&rows, column, &program, prover_label).into_keys() {
+            let program_rows: Vec<&Row> =
+                rows.iter().copied().filter(|r| program(r) == label).collect();
+            let mut sweep: BTreeMap<String, BTreeMap<u64, f64>> = BTreeMap::new();
+            for (shard_size, by_prover) in
+                means(&program_rows, column, |r| text(r, "shard_size").to_string(), prover_label)
+            {
+                let Ok(shard_size) = shard_size.parse::<u64>() else { continue };
+                for (prover, value) in by_prover {
+                    sweep.entry(prover).or_default().insert(shard_size, value);
+                }
+            }
+            if sweep.values().any(|points| points.len() > 1) {
+                let title = format!("{} core proving time by log2 shard size", label);
+                html.push_str(&line_chart(&title, "s", &sweep));
+                html.push('\n');
+            }
+        }
+    }
+    html.push_str("</body></html>\n");
+    html
+}
+
+/// Writes the Markdown and HTML reports of the result files.
+pub fn run(args: &ReportArgs) -> Result<()> {
+    let mut rows = Vec::new();
+    for file in &args.files {
+        rows.extend(read_rows(file)?);
+    }
+    if let Some(path) = &args.pricing {
+        Pricing::load(path)?.annotate(&mut rows);
+    }
+
+    for (extension, contents) in [("md", markdown(&rows)), ("html", html(&rows))] {
+        let path = args.output.with_extension(extension);
+        fs::write(&path, contents).map_err(|e| {
+            EvalError::Config(format!("failed to write report {}: {}", path.display(), e))
+        })?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(program: &str, prover: &str, shard_size: &str, duration: &str, status: &str) -> Row {
+        [
+            ("program", program),
+            ("prover", prover),
+            ("hashfn", "poseidon2"),
+            ("input_digest", "0123456789abcdef"),
+            ("shard_size", shard_size),
+            ("cycles", "1000"),
+            ("core_prove_duration_median", duration),
+            ("speed", "100"),
+            ("core_proof_size", "2048"),
+            ("compress_proof_size", "1024"),
+            ("instance_type", "g6.16xlarge"),
+            ("cpu_model", "AMD <EPYC>"),
+            ("status", status),
+        ]
+        .into_iter()
+        .map(|(column, value)| (column.to_string(), value.to_string()))
+        .collect()
+    }
+
+    #[test]
+    fn reports_group_successful_rows_by_machine() {
+        let rows = vec![
+            row("loop", "sp1", "21", "2.0", "ok"),
+            row("loop", "sp1", "22", "1.0", "ok"),
+            row("loop", "risc0", "21", "3.0", "ok"),
+            row("reth", "sp1", "22", "9.0", "proving_error"),
+        ];
+
+        let md = markdown(&rows);
+        assert!(md.contains("## g6.16xlarge (AMD <EPYC>)"));
+        assert!(md.contains("| loop | sp1 | poseidon2 | 22 | 1000 | 1.000 | 100 | 2048 | 1024 |"));
+        assert!(!md.contains("reth"));
+
+        let html = html(&rows);
+        assert!(html.contains("<h2>g6.16xlarge (AMD &lt;EPYC&gt;)</h2>"));
+        assert_eq!(html.matches("<svg").count(), BAR_CHARTS.len() + 1);
+        assert!(html.contains("loop core proving time by log2 shard size"));
+        assert!(!html.contains("src=") && !html.contains("href="));
+    }
+
+    #[test]
+    fn charts_keep_configurations_and_inputs_apart() {
+        let mut sha256 = row("loop", "sp1", "22", "4.0", "ok");
+        sha256.insert("hashfn".to_string(), "sha256".to_string());
+        let mut other_input = row("loop", "sp1", "22", "8.0", "ok");
+        other_input.insert("input_digest".to_string(), "fedcba9876543210".to_string());
+        let rows = [
+            row("loop", "sp1", "21", "2.0", "ok"),
+            row("loop", "sp1", "22", "1.0", "ok"),
+            sha256,
+            other_input,
+        ];
+        let rows: Vec<&Row> = rows.iter().collect();
+
+        let program = program_label(&rows);
+        let data = means(&rows, "core_prove_duration_median", &program, configuration_label);
+        let input = &data["loop (01234567)"];
+        assert_eq!(input["sp1 poseidon2 2^21"], 2.0);
+        assert_eq!(input["sp1 poseidon2 2^22"], 1.0);
+        assert_eq!(input["sp1 sha256 2^22"], 4.0);
+        assert_eq!(data["loop (fedcba98)"]["sp1 poseidon2 2^22"], 8.0);
+    }
+
+    #[test]
+    fn values_are_shortened() {
+        assert_eq!(short(1_500_000.0), "1.5M");
+        assert_eq!(short(2048.0), "2.048k");
+        assert_eq!(short(0.25), "0.25");
+    }
+}
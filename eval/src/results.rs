@@ -0,0 +1,712 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use csv::{ReaderBuilder, WriterBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    breakdown::ExecutionBreakdown,
+    environment::Environment,
+    error::{EvalError, Result},
+    stats::Summary,
+    timeline::Timeline,
+    EvalArgs, OutputFormat,
+};
+
+/// The version of the schema of the result files.
+///
+/// Bump it whenever a column of [`PerformanceReport`] or [`Sample`] is added, removed, renamed or
+/// changes meaning, so that files written by different binaries are never mixed, and note the
+/// changes of meaning in the README.
+pub const SCHEMA_VERSION: u32 = 13;
+
+/// The performance report of a zkVM on a program.
+///
+/// Every duration is summarized over the measured trials by the columns of its statistics.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct PerformanceReport {
+    /// The version of the schema the report was written with.
+    pub schema_version: u32,
+    /// The program that is being evaluated.
+    pub program: String,
+    /// The prover that is being evaluated.
+    pub prover: String,
+    /// The hash function that is being evaluated.
+    pub hashfn: String,
+    /// The shard size that is being evaluated.
+    pub shard_size: u64,
+    /// How far through the proving pipeline the run went.
+    pub mode: String,
+    /// Whether the proofs were real, or mocked with the proving and verification timings zeroed.
+    pub prover_mode: String,
+    /// The number of shards.
+    pub shards: usize,
+    /// The reported number of cycles.
+    ///
+    /// Note that this number may vary based on the zkVM.
+    pub cycles: u64,
+    /// The speed of the core prover in cycles per second, over the median core proving time.
+    pub speed: f64,
+    /// The reported speed of the executor in cycles per second.
+    pub execution_speed: f64,
+    /// The number of syscalls made by the program, if the zkVM reports it.
+    pub syscalls: Option<u64>,
+    /// The number of bytes hashed by the program, if it is a hashing benchmark.
+    pub bytes_hashed: Option<u64>,
+    /// The gas used by the block the program executed, if it is known from the block manifest.
+    pub gas_used: Option<u64>,
+    /// The speed of the prover in bytes hashed per second, if it is a hashing benchmark.
+    pub hash_speed: Option<f64>,
+    /// The speed of the executor in bytes hashed per second, if it is a hashing benchmark.
+    pub hash_execution_speed: Option<f64>,
+    /// The size of the seal of the core proof in bytes, see [`crate::proof_size::ProofSize`].
+    pub core_seal_size: usize,
+    /// The size of the core proof with its public values as serialized with bincode in bytes.
+    pub core_proof_size: usize,
+    /// The size of the public values of the core proof in bytes.
+    pub core_public_values_size: usize,
+    /// The size of the seal of the recursive proof in bytes.
+    pub compress_seal_size: usize,
+    /// The size of the recursive proof with its public values as serialized with bincode in bytes.
+    pub compress_proof_size: usize,
+    /// The size of the public values of the recursive proof in bytes.
+    pub compress_public_values_size: usize,
+    /// The SHA-256 digest of the evaluated ELF, in hex.
+    pub elf_digest: String,
+    /// The time spent building the ELF in seconds, zero if an up to date ELF was reused.
+    pub build_duration: f64,
+    /// The SHA-256 digest of the inputs as written to the guest, in hex.
+    pub input_digest: String,
+    /// The size of the inputs as written to the guest in bytes.
+    pub input_size: u64,
+    /// The number of measured trials.
+    pub trials: usize,
+    /// The number of warmup trials run before the measured ones.
+    pub warmup: usize,
+    /// The mean duration of the execution in seconds.
+    pub execution_duration_mean: f64,
+    /// The median duration of the execution in seconds.
+    pub execution_duration_median: f64,
+    /// The standard deviation of the duration of the execution in seconds.
+    pub execution_duration_stddev: f64,
+    /// The minimum duration of the execution in seconds.
+    pub execution_duration_min: f64,
+    /// The maximum duration of the execution in seconds.
+    pub execution_duration_max: f64,
+    /// The 95th percentile duration of the execution in seconds.
+    pub execution_duration_p95: f64,
+    /// The mean duration of the prover in seconds.
+    pub prove_duration_mean: f64,
+    /// The median duration of the prover in seconds.
+    pub prove_duration_median: f64,
+    /// The standard deviation of the duration of the prover in seconds.
+    pub prove_duration_stddev: f64,
+    /// The minimum duration of the prover in seconds.
+    pub prove_duration_min: f64,
+    /// The maximum duration of the prover in seconds.
+    pub prove_duration_max: f64,
+    /// The 95th percentile duration of the prover in seconds.
+    pub prove_duration_p95: f64,
+    /// The mean duration of the core proving time in seconds.
+    pub core_prove_duration_mean: f64,
+    /// The median duration of the core proving time in seconds.
+    pub core_prove_duration_median: f64,
+    /// The standard deviation of the duration of the core proving time in seconds.
+    pub core_prove_duration_stddev: f64,
+    /// The minimum duration of the core proving time in seconds.
+    pub core_prove_duration_min: f64,
+    /// The maximum duration of the core proving time in seconds.
+    pub core_prove_duration_max: f64,
+    /// The 95th percentile duration of the core proving time in seconds.
+    pub core_prove_duration_p95: f64,
+    /// The mean duration of the core verifier in seconds.
+    pub core_verify_duration_mean: f64,
+    /// The median duration of the core verifier in seconds.
+    pub core_verify_duration_median: f64,
+    /// The standard deviation of the duration of the core verifier in seconds.
+    pub core_verify_duration_stddev: f64,
+    /// The minimum duration of the core verifier in seconds.
+    pub core_verify_duration_min: f64,
+    /// The maximum duration of the core verifier in seconds.
+    pub core_verify_duration_max: f64,
+    /// The 95th percentile duration of the core verifier in seconds.
+    pub core_verify_duration_p95: f64,
+    /// The mean duration of the recursive proving time in seconds.
+    pub compress_prove_duration_mean: f64,
+    /// The median duration of the recursive proving time in seconds.
+    pub compress_prove_duration_median: f64,
+    /// The standard deviation of the duration of the recursive proving time in seconds.
+    pub compress_prove_duration_stddev: f64,
+    /// The minimum duration of the recursive proving time in seconds.
+    pub compress_prove_duration_min: f64,
+    /// The maximum duration of the recursive proving time in seconds.
+    pub compress_prove_duration_max: f64,
+    /// The 95th percentile duration of the recursive proving time in seconds.
+    pub compress_prove_duration_p95: f64,
+    /// The mean duration of the recursive verifier in seconds.
+    pub compress_verify_duration_mean: f64,
+    /// The median duration of the recursive verifier in seconds.
+    pub compress_verify_duration_median: f64,
+    /// The standard deviation of the duration of the recursive verifier in seconds.
+    pub compress_verify_duration_stddev: f64,
+    /// The minimum duration of the recursive verifier in seconds.
+    pub compress_verify_duration_min: f64,
+    /// The maximum duration of the recursive verifier in seconds.
+    pub compress_verify_duration_max: f64,
+    /// The 95th percentile duration of the recursive verifier in seconds.
+    pub compress_verify_duration_p95: f64,
+    /// The peak resident set size during the execution in bytes.
+    pub execution_peak_rss: u64,
+    /// The CPU time used by the execution in seconds.
+    pub execution_cpu_time: f64,
+    /// The average number of CPU cores used by the execution.
+    pub execution_cpu_cores: f64,
+    /// The peak resident set size during the core proving in bytes.
+    pub core_prove_peak_rss: u64,
+    /// The CPU time used by the core proving in seconds.
+    pub core_prove_cpu_time: f64,
+    /// The average number of CPU cores used by the core proving.
+    pub core_prove_cpu_cores: f64,
+    /// The peak resident set size during the core verifier in bytes.
+    pub core_verify_peak_rss: u64,
+    /// The CPU time used by the core verifier in seconds.
+    pub core_verify_cpu_time: f64,
+    /// The average number of CPU cores used by the core verifier.
+    pub core_verify_cpu_cores: f64,
+    /// The peak resident set size during the recursive proving in bytes.
+    pub compress_prove_peak_rss: u64,
+    /// The CPU time used by the recursive proving in seconds.
+    pub compress_prove_cpu_time: f64,
+    /// The average number of CPU cores used by the recursive proving.
+    pub compress_prove_cpu_cores: f64,
+    /// The peak resident set size during the recursive verifier in bytes.
+    pub compress_verify_peak_rss: u64,
+    /// The CPU time used by the recursive verifier in seconds.
+    pub compress_verify_cpu_time: f64,
+    /// The average number of CPU cores used by the recursive verifier.
+    pub compress_verify_cpu_cores: f64,
+    /// Whether every tampered proof was rejected by the verifier, if the tamper check ran.
+    pub tamper_check: Option<bool>,
+    /// The outcome of the run: `ok`, or the kind of error that stopped it.
+    pub status: String,
+    /// The error message if the run failed.
+    pub error: String,
+    /// The instance type the run was on, if known.
+    pub instance_type: String,
+    /// The model name of the CPU.
+    pub cpu_model: String,
+    /// The number of CPU cores available to the process.
+    pub cpu_cores: usize,
+    /// Whether the CPU supports AVX-512.
+    pub avx512: bool,
+    /// The total memory of the machine in bytes.
+    pub total_memory: u64,
+    /// The release of the operating system kernel.
+    pub kernel: String,
+    /// The version of rustc the binary was built with.
+    pub rustc_version: String,
+    /// The resolved version of the SP1 prover crate.
+    pub sp1_version: String,
+    /// The resolved version of the RISC0 zkVM crate.
+    pub risc0_version: String,
+    /// The git commit the binary was built from.
+    pub git_sha: String,
+    /// The samples of every trial, including the warmup ones.
+    #[serde(skip)]
+    pub samples: Vec<Sample>,
+    /// What the last execution spent its cycles on, written to a companion file of its own.
+    #[serde(skip)]
+    pub breakdown: Option<ExecutionBreakdown>,
+    /// How the last core proof split its work over the shards, written to a companion file of its
+    /// own.
+    #[serde(skip)]
+    pub timeline: Option<Timeline>,
+}
+
+/// The durations measured in a single trial.
+///
+/// A duration is `None` if the trial failed before reaching the phase.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct Sample {
+    /// The version of the schema the sample was written with.
+    pub schema_version: u32,
+    /// The program that is being evaluated.
+    pub program: String,
+    /// The prover that is being evaluated.
+    pub prover: String,
+    /// The hash function that is being evaluated.
+    pub hashfn: String,
+    /// The shard size that is being evaluated.
+    pub shard_size: u64,
+    /// The index of the trial, counting the warmup trials.
+    pub trial: usize,
+    /// Whether the trial is a warmup trial, excluded from the statistics.
+    pub warmup: bool,
+    /// The duration of the execution in seconds.
+    pub execution_duration: Option<f64>,
+    /// The duration of the prover in seconds.
+    pub prove_duration: Option<f64>,
+    /// The duration of the core proving time in seconds.
+    pub core_prove_duration: Option<f64>,
+    /// The duration of the core verifier in seconds.
+    pub core_verify_duration: Option<f64>,
+    /// The duration of the recursive proving time in seconds.
+    pub compress_prove_duration: Option<f64>,
+    /// The duration of the recursive verifier in seconds.
+    pub compress_verify_duration: Option<f64>,
+    /// The peak resident set size during the execution in bytes.
+    pub execution_peak_rss: Option<u64>,
+    /// The CPU time used by the execution in seconds.
+    pub execution_cpu_time: Option<f64>,
+    /// The average number of CPU cores used by the execution.
+    pub execution_cpu_cores: Option<f64>,
+    /// The peak resident set size during the core proving in bytes.
+    pub core_prove_peak_rss: Option<u64>,
+    /// The CPU time used by the core proving in seconds.
+    pub core_prove_cpu_time: Option<f64>,
+    /// The average number of CPU cores used by the core proving.
+    pub core_prove_cpu_cores: Option<f64>,
+    /// The peak resident set size during the core verifier in bytes.
+    pub core_verify_peak_rss: Option<u64>,
+    /// The CPU time used by the core verifier in seconds.
+    pub core_verify_cpu_time: Option<f64>,
+    /// The average number of CPU cores used by the core verifier.
+    pub core_verify_cpu_cores: Option<f64>,
+    /// The peak resident set size during the recursive proving in bytes.
+    pub compress_prove_peak_rss: Option<u64>,
+    /// The CPU time used by the recursive proving in seconds.
+    pub compress_prove_cpu_time: Option<f64>,
+    /// The average number of CPU cores used by the recursive proving.
+    pub compress_prove_cpu_cores: Option<f64>,
+    /// The peak resident set size during the recursive verifier in bytes.
+    pub compress_verify_peak_rss: Option<u64>,
+    /// The CPU time used by the recursive verifier in seconds.
+    pub compress_verify_cpu_time: Option<f64>,
+    /// The average number of CPU cores used by the recursive verifier.
+    pub compress_verify_cpu_cores: Option<f64>,
+}
+
+impl PerformanceReport {
+    /// Creates an empty report for the configuration being evaluated.
+    pub fn new(args: &EvalArgs) -> Self {
+        let environment = Environment::current();
+        Self {
+            schema_version: SCHEMA_VERSION,
+            program: args.program.clone(),
+            prover: args.prover.to_string(),
+            hashfn: args.hashfn.to_string(),
+            shard_size: args.shard_size,
+            mode: args.mode.to_string(),
+            prover_mode: args.prover_mode.to_string(),
+            trials: args.trials,
+            warmup: args.warmup,
+            status: "ok".to_string(),
+            instance_type: environment.instance_type,
+            cpu_model: environment.cpu_model,
+            cpu_cores: environment.cpu_cores,
+            avx512: environment.avx512,
+            total_memory: environment.total_memory,
+            kernel: environment.kernel,
+            rustc_version: environment.rustc_version,
+            sp1_version: environment.sp1_version,
+            risc0_version: environment.risc0_version,
+            git_sha: environment.git_sha,
+            ..Default::default()
+        }
+    }
+
+    /// Creates an empty sample for a trial of this report's configuration.
+    pub fn sample(&self, trial: usize) -> Sample {
+        Sample {
+            schema_version: SCHEMA_VERSION,
+            program: self.program.clone(),
+            prover: self.prover.clone(),
+            hashfn: self.hashfn.clone(),
+            shard_size: self.shard_size,
+            trial,
+            warmup: trial < self.warmup,
+            ..Default::default()
+        }
+    }
+
+    /// Updates the duration, resource usage and speed columns from the samples of the measured
+    /// trials.
+    pub fn summarize(&mut self) {
+        let measured: Vec<&Sample> = self.samples.iter().filter(|s| !s.warmup).collect();
+        let summarize = |duration: fn(&Sample) -> Option<f64>| {
+            Summary::of(&measured.iter().filter_map(|s| duration(s)).collect::<Vec<_>>())
+        };
+
+        let summary = summarize(|s| s.execution_duration);
+        self.execution_duration_mean = summary.mean;
+        self.execution_duration_median = summary.median;
+        self.execution_duration_stddev = summary.stddev;
+        self.execution_duration_min = summary.min;
+        self.execution_duration_max = summary.max;
+        self.execution_duration_p95 = summary.p95;
+
+        let summary = summarize(|s| s.prove_duration);
+        self.prove_duration_mean = summary.mean;
+        self.prove_duration_median = summary.median;
+        self.prove_duration_stddev = summary.stddev;
+        self.prove_duration_min = summary.min;
+        self.prove_duration_max = summary.max;
+        self.prove_duration_p95 = summary.p95;
+
+        let summary = summarize(|s| s.core_prove_duration);
+        self.core_prove_duration_mean = summary.mean;
+        self.core_prove_duration_median = summary.median;
+        self.core_prove_duration_stddev = summary.stddev;
+        self.core_prove_duration_min = summary.min;
+        self.core_prove_duration_max = summary.max;
+        self.core_prove_duration_p95 = summary.p95;
+
+        let summary = summarize(|s| s.core_verify_duration);
+        self.core_verify_duration_mean = summary.mean;
+        self.core_verify_duration_median = summary.median;
+        self.core_verify_duration_stddev = summary.stddev;
+        self.core_verify_duration_min = summary.min;
+        self.core_verify_duration_max = summary.max;
+        self.core_verify_duration_p95 = summary.p95;
+
+        let summary = summarize(|s| s.compress_prove_duration);
+        self.compress_prove_duration_mean = summary.mean;
+        self.compress_prove_duration_median = summary.median;
+        self.compress_prove_duration_stddev = summary.stddev;
+        self.compress_prove_duration_min = summary.min;
+        self.compress_prove_duration_max = summary.max;
+        self.compress_prove_duration_p95 = summary.p95;
+
+        let summary = summarize(|s| s.compress_verify_duration);
+        self.compress_verify_duration_mean = summary.mean;
+        self.compress_verify_duration_median = summary.median;
+        self.compress_verify_duration_stddev = summary.stddev;
+        self.compress_verify_duration_min = summary.min;
+        self.compress_verify_duration_max = summary.max;
+        self.compress_verify_duration_p95 = summary.p95;
+
+        // The resource usage is the peak memory and the mean CPU usage over the measured trials.
+        let peak_rss = |rss: fn(&Sample) -> Option<u64>| {
+            measured.iter().filter_map(|s| rss(s)).max().unwrap_or_default()
+        };
+        self.execution_peak_rss = peak_rss(|s| s.execution_peak_rss);
+        self.execution_cpu_time = summarize(|s| s.execution_cpu_time).mean;
+        self.execution_cpu_cores = summarize(|s| s.execution_cpu_cores).mean;
+        self.core_prove_peak_rss = peak_rss(|s| s.core_prove_peak_rss);
+        self.core_prove_cpu_time = summarize(|s| s.core_prove_cpu_time).mean;
+        self.core_prove_cpu_cores = summarize(|s| s.core_prove_cpu_cores).mean;
+        self.core_verify_peak_rss = peak_rss(|s| s.core_verify_peak_rss);
+        self.core_verify_cpu_time = summarize(|s| s.core_verify_cpu_time).mean;
+        self.core_verify_cpu_cores = summarize(|s| s.core_verify_cpu_cores).mean;
+        self.compress_prove_peak_rss = peak_rss(|s| s.compress_prove_peak_rss);
+        self.compress_prove_cpu_time = summarize(|s| s.compress_prove_cpu_time).mean;
+        self.compress_prove_cpu_cores = summarize(|s| s.compress_prove_cpu_cores).mean;
+        self.compress_verify_peak_rss = peak_rss(|s| s.compress_verify_peak_rss);
+        self.compress_verify_cpu_time = summarize(|s| s.compress_verify_cpu_time).mean;
+        self.compress_verify_cpu_cores = summarize(|s| s.compress_verify_cpu_cores).mean;
+
+        if self.execution_duration_median > 0.0 {
+            self.execution_speed = (self.cycles as f64) / self.execution_duration_median;
+        }
+        if self.core_prove_duration_median > 0.0 {
+            self.speed = (self.cycles as f64) / self.core_prove_duration_median;
+        }
+        if let Some(bytes_hashed) = self.bytes_hashed {
+            let per_second =
+                |duration: f64| (duration > 0.0).then(|| bytes_hashed as f64 / duration);
+            self.hash_execution_speed = per_second(self.execution_duration_median);
+            self.hash_speed = per_second(self.core_prove_duration_median);
+        }
+    }
+
+    /// Returns the number of measured trials that completed, which leaves out the trial a failed
+    /// run failed in.
+    pub fn completed_trials(&self) -> usize {
+        let measured = self.samples.iter().filter(|s| !s.warmup).count();
+        measured.saturating_sub(usize::from(self.status != "ok"))
+    }
+
+    /// Marks the report as failed with the given error.
+    pub fn fail(&mut self, err: &EvalError) {
+        self.status = err.status().to_string();
+        self.error = err.to_string();
+    }
+}
+
+/// A record of a companion file of the result files, with the columns that join it with the
+/// report row of its run.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompanionRecord<T> {
+    /// The version of the schema the record was written with.
+    pub schema_version: u32,
+    /// The program that is being evaluated.
+    pub program: String,
+    /// The prover that is being evaluated.
+    pub prover: String,
+    /// The hash function that is being evaluated.
+    pub hashfn: String,
+    /// The shard size that is being evaluated.
+    pub shard_size: u64,
+    /// The SHA-256 digest of the inputs as written to the guest, in hex.
+    pub input_digest: String,
+    /// The data of the record.
+    #[serde(flatten)]
+    pub data: T,
+}
+
+impl<T: Clone> CompanionRecord<T> {
+    /// Creates the record of the data of the report's run.
+    pub fn new(report: &PerformanceReport, data: &T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            program: report.program.clone(),
+            prover: report.prover.clone(),
+            hashfn: report.hashfn.clone(),
+            shard_size: report.shard_size,
+            input_digest: report.input_digest.clone(),
+            data: data.clone(),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// The extensions of the files written in this format.
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            OutputFormat::Csv => &["csv"],
+            OutputFormat::Jsonl => &["jsonl"],
+            OutputFormat::Both => &["csv", "jsonl"],
+        }
+    }
+}
+
+/// Returns the paths of the files named `stem` in `dir` for every extension of the format.
+pub fn result_paths(dir: &Path, stem: &str, format: OutputFormat) -> Vec<PathBuf> {
+    format.extensions().iter().map(|ext| dir.join(format!("{}.{}", stem, ext))).collect()
+}
+
+/// Checks that records of type `T` can be appended to the file without mixing schemas.
+///
+/// A CSV file must have exactly the header `T` serializes to, and the first line of a JSONL file
+/// must have the current schema version. Missing and empty files are always compatible.
+pub fn check_compatible<T: Serialize + Default>(path: &Path) -> Result<()> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(_) => return Ok(()),
+    };
+    let mismatch = |found: String| {
+        EvalError::Config(format!(
+            "{} was written with a different schema ({}); use another --filename",
+            path.display(),
+            found
+        ))
+    };
+
+    if is_jsonl(path) {
+        let mut line = String::new();
+        BufReader::new(file)
+            .read_line(&mut line)
+            .map_err(|e| EvalError::Config(format!("failed to read {}: {}", path.display(), e)))?;
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        let version = serde_json::from_str::<serde_json::Value>(&line)
+            .ok()
+            .and_then(|row| row.get("schema_version").and_then(|v| v.as_u64()));
+        match version {
+            Some(version) if version == SCHEMA_VERSION as u64 => Ok(()),
+            Some(version) => Err(mismatch(format!("schema version {}", version))),
+            None => Err(mismatch("no schema version".to_string())),
+        }
+    } else {
+        let mut reader = ReaderBuilder::new().has_headers(false).from_reader(file);
+        let header = match reader.records().next() {
+            Some(record) => record.map_err(|e| {
+                EvalError::Config(format!("failed to read {}: {}", path.display(), e))
+            })?,
+            None => return Ok(()),
+        };
+        let expected = csv_header::<T>();
+        if header.iter().eq(expected.iter().map(String::as_str)) {
+            Ok(())
+        } else {
+            Err(mismatch(format!("header `{}`", header.iter().collect::<Vec<_>>().join(","))))
+        }
+    }
+}
+
+/// Appends the records to the file, in CSV or JSONL depending on its extension.
+///
+/// The CSV header is only written if the file is new.
+pub fn append<T: Serialize>(path: &Path, records: &[T]) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    if is_jsonl(path) {
+        for record in records {
+            serde_json::to_writer(&mut file, record)?;
+            file.write_all(b"\n")?;
+        }
+        return file.flush();
+    }
+
+    let mut writer =
+        WriterBuilder::new().has_headers(file.metadata()?.len() == 0).from_writer(&file);
+    for record in records {
+        writer.serialize(record)?;
+    }
+    writer.flush()
+}
+
+/// A row of a result file, as its values by column, whatever the schema it was written with.
+pub type Row = BTreeMap<String, String>;
+
+/// Reads the rows of a result file, in CSV or JSONL depending on its extension.
+///
+/// Missing values (e.g. empty CSV fields or JSON nulls) are read as empty strings.
+pub fn read_rows(path: &Path) -> Result<Vec<Row>> {
+    let read_error =
+        |e: String| EvalError::Config(format!("failed to read {}: {}", path.display(), e));
+    let contents = fs::read_to_string(path).map_err(|e| read_error(e.to_string()))?;
+
+    if is_jsonl(path) {
+        return contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let row: BTreeMap<String, serde_json::Value> =
+                    serde_json::from_str(line).map_err(|e| read_error(e.to_string()))?;
+                Ok(row
+                    .into_iter()
+                    .map(|(column, value)| {
+                        let value = match value {
+                            serde_json::Value::Null => String::new(),
+                            serde_json::Value::String(value) => value,
+                            value => value.to_string(),
+                        };
+                        (column, value)
+                    })
+                    .collect())
+            })
+            .collect();
+    }
+
+    let mut reader = ReaderBuilder::new().from_reader(contents.as_bytes());
+    let header = reader.headers().map_err(|e| read_error(e.to_string()))?.clone();
+    reader
+        .records()
+        .map(|record| {
+            let record = record.map_err(|e| read_error(e.to_string()))?;
+            Ok(header.iter().map(String::from).zip(record.iter().map(String::from)).collect())
+        })
+        .collect()
+}
+
+/// Returns the CSV header of the records of type `T`.
+fn csv_header<T: Serialize + Default>() -> Vec<String> {
+    let mut writer = WriterBuilder::new().from_writer(vec![]);
+    writer.serialize(T::default()).expect("records serialize to csv");
+    let data = writer.into_inner().expect("writing to memory cannot fail");
+    let mut reader = ReaderBuilder::new().from_reader(data.as_slice());
+    reader.headers().expect("header was just written").iter().map(String::from).collect()
+}
+
+fn is_jsonl(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "jsonl")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("eval-{}-{}", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn appended_files_stay_compatible() {
+        for name in ["appended.csv", "appended.jsonl"] {
+            let path = temp_path(name);
+            check_compatible::<Sample>(&path).unwrap();
+
+            let sample = Sample { schema_version: SCHEMA_VERSION, ..Default::default() };
+            append(&path, &[sample.clone(), sample.clone()]).unwrap();
+            append(&path, &[sample]).unwrap();
+            check_compatible::<Sample>(&path).unwrap();
+
+            let contents = fs::read_to_string(&path).unwrap();
+            let header_lines = if name.ends_with(".csv") { 1 } else { 0 };
+            assert_eq!(contents.lines().count(), 3 + header_lines);
+            fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn rows_are_read_from_either_format() {
+        for name in ["rows.csv", "rows.jsonl"] {
+            let path = temp_path(name);
+            let report = PerformanceReport { program: "loop".to_string(), ..Default::default() };
+            append(&path, &[report]).unwrap();
+
+            let rows = read_rows(&path).unwrap();
+            assert_eq!(rows.len(), 1);
+            assert_eq!(rows[0]["program"], "loop");
+            assert_eq!(rows[0]["cycles"], "0");
+            assert_eq!(rows[0]["syscalls"], "");
+            fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn failed_trials_are_not_completed() {
+        // One warmup trial and three measured ones, the last of which may fail.
+        let mut report =
+            PerformanceReport { warmup: 1, status: "ok".to_string(), ..Default::default() };
+        report.samples = (0..4).map(|trial| report.sample(trial)).collect();
+        assert_eq!(report.completed_trials(), 3);
+
+        report.fail(&EvalError::Proving("trial 3 failed".to_string()));
+        assert_eq!(report.completed_trials(), 2);
+        report.samples.truncate(1);
+        assert_eq!(report.completed_trials(), 0);
+    }
+
+    #[test]
+    fn durations_are_summarized_by_their_statistics() {
+        let header = csv_header::<PerformanceReport>();
+        let start = header.iter().position(|column| column == "core_verify_duration_mean").unwrap();
+        let statistics = ["_mean", "_median", "_stddev", "_min", "_max", "_p95"];
+        assert_eq!(
+            header[start..start + statistics.len()],
+            statistics.map(|statistic| format!("core_verify_duration{}", statistic))
+        );
+
+        let samples = [3.0, 1.0, 2.0]
+            .map(|duration| Sample { core_verify_duration: Some(duration), ..Default::default() })
+            .to_vec();
+        let mut report = PerformanceReport { samples, ..Default::default() };
+        report.summarize();
+        assert_eq!(
+            (report.core_verify_duration_median, report.core_verify_duration_min),
+            (2.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn mismatched_files_are_rejected() {
+        let path = temp_path("mismatched.csv");
+        fs::write(&path, "program,prover,hashfn\nloop,sp1,poseidon\n").unwrap();
+        assert!(matches!(check_compatible::<PerformanceReport>(&path), Err(EvalError::Config(_))));
+        fs::remove_file(&path).unwrap();
+
+        let path = temp_path("mismatched.jsonl");
+        fs::write(&path, "{\"schema_version\":0}\n").unwrap();
+        assert!(matches!(check_compatible::<PerformanceReport>(&path), Err(EvalError::Config(_))));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,126 +1,301 @@
-#[cfg(feature = "risc0")]
-use std::fs;
+use std::{cell::RefCell, rc::Rc, time::Instant};
 
-#[cfg(feature = "risc0")]
 use risc0_zkvm::{
-    compute_image_id, get_prover_server, ExecutorEnv, ExecutorImpl, ProverOpts, VerifierContext,
-};
-#[cfg(feature = "risc0")]
-use crate::{
-    utils::{get_elf, get_reth_input, time_operation},
-    HashFnId, ProgramId,
+    compute_image_id, get_prover_server, Digest, ExecutorEnv, ExecutorImpl, ProverOpts,
+    ProverServer, Receipt, Segment, Session, SessionEvents, VerifierContext,
 };
 
 use crate::{
-    EvalArgs, PerformanceReport,
+    breakdown::ExecutionBreakdown,
+    error::{EvalError, Result},
+    evaluator::{
+        Capabilities, CompressedProofOutput, CoreProofOutput, Evaluator, ExecutionOutput,
+        GuestInput, HashSuite, Program,
+    },
+    proof_size::{serialized_size, ProofSize},
+    tamper::{all_rejected, flip_bytes, tampered},
+    timeline::ShardTiming,
+    EvalArgs, HashFnId, ProverMode,
 };
 
-pub struct Risc0Evaluator;
+/// The state created during setup and shared by the later phases.
+struct Risc0Setup {
+    prover: Rc<dyn ProverServer>,
+    elf: Vec<u8>,
+    image_id: Digest,
+    /// The inputs, serialized to the bytes written to the guest in order.
+    inputs: Vec<Vec<u8>>,
+    segment_limit_po2: u32,
+    /// Whether the prover runs in dev mode, which returns fake receipts without seals.
+    mock: bool,
+}
+
+/// The segments proven so far, timed by the hooks of the session.
+struct SegmentTimeline {
+    /// When the proof started, which the segments are timed from.
+    start: Instant,
+    /// When the segment being proven started.
+    segment_start: Option<Instant>,
+    /// The segments proven so far.
+    segments: Vec<ShardTiming>,
+}
+
+/// Times the segments of a session as they are proven.
+struct SegmentTimer(Rc<RefCell<SegmentTimeline>>);
+
+impl SessionEvents for SegmentTimer {
+    fn on_pre_prove_segment(&self, _segment: &Segment) {
+        self.0.borrow_mut().segment_start = Some(Instant::now());
+    }
+
+    fn on_post_prove_segment(&self, segment: &Segment) {
+        let mut timeline = self.0.borrow_mut();
+        let Some(segment_start) = timeline.segment_start.take() else { return };
+        let start = segment_start.saturating_duration_since(timeline.start).as_secs_f64();
+        let proving = segment_start.elapsed().as_secs_f64();
+        timeline.segments.push(ShardTiming {
+            index: segment.index as u64,
+            padded_cycles: Some(1 << segment.po2),
+            start: Some(start),
+            end: Some(start + proving),
+            proving: Some(proving),
+            ..Default::default()
+        });
+    }
+}
+
+#[derive(Default)]
+pub struct Risc0Evaluator {
+    setup: Option<Risc0Setup>,
+    session: Option<Session>,
+    /// The timeline of the segments of the session, filled in while it is proven.
+    timeline: Option<Rc<RefCell<SegmentTimeline>>>,
+    receipt: Option<Receipt>,
+    compressed_receipt: Option<Receipt>,
+}
 
 impl Risc0Evaluator {
-    #[cfg(feature = "risc0")]
-    pub fn eval(args: &EvalArgs) -> PerformanceReport {
-        if args.hashfn != HashFnId::Poseidon {
-            panic!("Only Poseidon hash function is supported for Risc0.");
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The options supported by RISC0, which proves with a fixed segment size in sweeps.
+    ///
+    /// Segments can be proven with any of the hash suites of the prover, but recursion only
+    /// supports Poseidon2, so the other suites can't be compressed.
+    pub fn capabilities() -> Capabilities {
+        let suite = |hashfn, name, compress| HashSuite { hashfn, name, compress };
+        Capabilities {
+            hashfns: vec![
+                suite(HashFnId::Poseidon, "poseidon2", true),
+                suite(HashFnId::Sha256, "sha-256", false),
+                suite(HashFnId::Blake2b, "blake2b", false),
+            ],
+            shard_sizes: false,
+            version: risc0_zkvm::VERSION,
         }
+    }
 
-        let elf_path = get_elf(args);
-        let elf = fs::read(&elf_path).unwrap();
-        let image_id = compute_image_id(elf.as_slice()).unwrap();
-
-        // If the program is Reth, read the block and set it as input. Otherwise, we assume other
-        // benchmarking programs don't have input.
-        let env = if args.program == ProgramId::Reth {
-            let input = get_reth_input(args);
-            ExecutorEnv::builder()
-                .segment_limit_po2(args.shard_size as u32)
-                .write(&input)
-                .expect("Failed to write input to executor")
-                .build()
-                .unwrap()
-        } else {
-            ExecutorEnv::builder().segment_limit_po2(args.shard_size as u32).build().unwrap()
-        };
+    fn state(&self) -> &Risc0Setup {
+        self.setup.as_ref().expect("setup must run before the other phases")
+    }
+
+    /// Returns whether every tampered copy of the receipt and of its journal fails to verify.
+    fn rejects_tampered(&self, receipt: &Receipt) -> bool {
+        let image_id = self.state().image_id;
+
+        let journals: Vec<Receipt> = flip_bytes(&receipt.journal.bytes)
+            .into_iter()
+            .map(|journal| {
+                let mut receipt = receipt.clone();
+                receipt.journal.bytes = journal;
+                receipt
+            })
+            .collect();
+
+        let verify = |receipt: &Receipt| receipt.verify(image_id);
+        all_rejected(&tampered(receipt), verify) && all_rejected(&journals, verify)
+    }
+}
 
-        // Compute some statistics.
-        let mut exec = ExecutorImpl::from_elf(env, &elf).unwrap();
-        let session = exec.run().unwrap();
-        let cycles = session.user_cycles;
-
-        // Setup the prover.
-        let env = if args.program == ProgramId::Reth {
-            let input = get_reth_input(args);
-            ExecutorEnv::builder()
-                .segment_limit_po2(args.shard_size as u32)
-                .write(&input)
-                .expect("Failed to write input to executor")
-                .build()
-                .unwrap()
+impl Evaluator for Risc0Evaluator {
+    fn setup(&mut self, args: &EvalArgs, program: &Program) -> Result<()> {
+        let capabilities = Self::capabilities();
+        let hashfn = capabilities.hash_suite(args).map_err(EvalError::Config)?.name;
+
+        let image_id = compute_image_id(program.elf.as_slice())
+            .map_err(|e| EvalError::Config(format!("failed to compute image id: {}", e)))?;
+        let inputs = program
+            .inputs
+            .iter()
+            .map(|input| {
+                let words = match input {
+                    GuestInput::Reth(input) => risc0_zkvm::serde::to_vec(input.as_ref()),
+                    GuestInput::Sha2Chain(input) => risc0_zkvm::serde::to_vec(input),
+                    GuestInput::Bytes(bytes) => risc0_zkvm::serde::to_vec(bytes),
+                    GuestInput::Value(value) => risc0_zkvm::serde::to_vec(value),
+                    // The guest can't deserialize bincode from words, so it reads the length of
+                    // the value as a word and then its bytes as they are.
+                    GuestInput::Bincode(bytes) => {
+                        let length = (bytes.len() as u32).to_le_bytes();
+                        return Ok([length.as_slice(), bytes].concat());
+                    }
+                }
+                .map_err(|e| EvalError::Config(format!("failed to serialize input: {}", e)))?;
+                Ok(words.iter().flat_map(|word| word.to_le_bytes()).collect())
+            })
+            .collect::<Result<_>>()?;
+
+        // Dev mode fakes the receipts, and must be set while the prover is created and while the
+        // receipts are verified. Clear it for real proofs, so it is never inherited by accident.
+        let mock = args.prover_mode == ProverMode::Mock;
+        if mock {
+            std::env::set_var("RISC0_DEV_MODE", "1");
         } else {
-            ExecutorEnv::builder().segment_limit_po2(args.shard_size as u32).build().unwrap()
+            std::env::remove_var("RISC0_DEV_MODE");
+        }
+
+        // Setup the prover with the hash suite the segments are proven with.
+        let opts = ProverOpts { hashfn: hashfn.to_string(), ..ProverOpts::default() };
+        let prover = get_prover_server(&opts)
+            .map_err(|e| EvalError::Config(format!("failed to initialize prover: {}", e)))?;
+
+        self.setup = Some(Risc0Setup {
+            prover,
+            elf: program.elf.clone(),
+            image_id,
+            inputs,
+            segment_limit_po2: args.shard_size as u32,
+            mock,
+        });
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<ExecutionOutput> {
+        let setup = self.state();
+
+        let mut builder = ExecutorEnv::builder();
+        builder.segment_limit_po2(setup.segment_limit_po2);
+        for input in &setup.inputs {
+            builder.write_slice(input);
+        }
+        let env = builder.build().map_err(|e| EvalError::Execution(e.to_string()))?;
+
+        // Generate the session, and time its segments when it is proven.
+        let mut session = ExecutorImpl::from_elf(env, &setup.elf)
+            .and_then(|mut exec| exec.run())
+            .map_err(|e| EvalError::Execution(e.to_string()))?;
+        let timeline = Rc::new(RefCell::new(SegmentTimeline {
+            start: Instant::now(),
+            segment_start: None,
+            segments: Vec::new(),
+        }));
+        session.add_hook(SegmentTimer(timeline.clone()));
+        let output = ExecutionOutput {
+            cycles: session.user_cycles,
+            shards: session.segments.len(),
+            syscalls: None,
+            breakdown: ExecutionBreakdown {
+                user_cycles: Some(session.user_cycles),
+                paging_cycles: Some(session.paging_cycles),
+                reserved_cycles: Some(session.reserved_cycles),
+                total_cycles: Some(session.total_cycles),
+                ..Default::default()
+            },
         };
-        let opts = ProverOpts::default();
-        let prover = get_prover_server(&opts).unwrap();
+        self.session = Some(session);
+        self.timeline = Some(timeline);
+        Ok(output)
+    }
 
-        // Generate the session.
-        let mut exec = ExecutorImpl::from_elf(env, &elf).unwrap();
-        let (session, execution_duration) = time_operation(|| exec.run().unwrap());
+    fn prove_core(&mut self) -> Result<CoreProofOutput> {
+        let setup = self.state();
+        let session = self.session.as_ref().expect("execute must run before prove_core");
+        let timeline = self.timeline.as_ref().expect("execute must run before prove_core");
+        {
+            let mut timeline = timeline.borrow_mut();
+            timeline.start = Instant::now();
+            timeline.segments.clear();
+        }
 
         // Generate the proof.
         let ctx = VerifierContext::default();
-        let (info, core_prove_duration) =
-            time_operation(|| prover.prove_session(&ctx, &session).unwrap());
-
+        let info = setup
+            .prover
+            .prove_session(&ctx, session)
+            .map_err(|e| EvalError::Proving(e.to_string()))?;
         let receipt = info.receipt;
 
-        let composite_receipt = receipt.inner.composite().unwrap();
-        let num_segments = composite_receipt.segments.len();
+        // The seal of the core proof is made of the seals of all segments, serialized like the
+        // shard proofs of SP1. The fake receipts of dev mode have neither.
+        let (num_segments, seal) = if setup.mock {
+            (session.segments.len(), 0)
+        } else {
+            let composite_receipt =
+                receipt.inner.composite().map_err(|e| EvalError::Proving(e.to_string()))?;
+            let segments = &composite_receipt.segments;
+            (segments.len(), segments.iter().map(|segment| serialized_size(&segment.seal)).sum())
+        };
+        let proof_size = ProofSize {
+            seal,
+            receipt: serialized_size(&receipt),
+            public_values: receipt.journal.bytes.len(),
+        };
 
-        // Get the core proof size by summing across all segments.
-        let mut core_proof_size = 0;
-        for segment in composite_receipt.segments.iter() {
-            core_proof_size += segment.seal.len() * 4;
-        }
+        let shard_timings = std::mem::take(&mut timeline.borrow_mut().segments);
+        self.receipt = Some(receipt);
+        Ok(CoreProofOutput { shards: num_segments, proof_size, shard_timings })
+    }
 
-        // Verify the core proof.
-        let ((), core_verify_duration) = time_operation(|| receipt.verify(image_id).unwrap());
+    fn verify_core(&mut self) -> Result<()> {
+        let image_id = self.state().image_id;
+        let receipt = self.receipt.as_ref().expect("prove_core must run before verify_core");
+        receipt.verify(image_id).map_err(|e| EvalError::Verification(e.to_string()))
+    }
+
+    fn compress(&mut self) -> Result<CompressedProofOutput> {
+        let setup = self.state();
+        let receipt = self.receipt.as_ref().expect("prove_core must run before compress");
 
         // Now compress the proof with recursion.
-        let (compressed_proof, compress_duration) =
-            time_operation(|| prover.compress(&ProverOpts::succinct(), &receipt).unwrap());
-
-        // Verify the recursive proof
-        let ((), recursive_verify_duration) =
-            time_operation(|| compressed_proof.verify(image_id).unwrap());
-
-        let succinct_receipt = compressed_proof.inner.succinct().unwrap();
-
-        // Get the recursive proof size.
-        let recursive_proof_size = succinct_receipt.seal.len() * 4;
-        let prove_duration = core_prove_duration + compress_duration;
-
-        // Create the performance report.
-        PerformanceReport {
-            program: args.program.to_string(),
-            prover: args.prover.to_string(),
-            hashfn: args.hashfn.to_string(),
-            shard_size: args.shard_size,
-            shards: num_segments,
-            cycles: cycles as u64,
-            speed: (cycles as f64) / prove_duration.as_secs_f64(),
-            execution_duration: execution_duration.as_secs_f64(),
-            prove_duration: prove_duration.as_secs_f64(),
-            core_prove_duration: core_prove_duration.as_secs_f64(),
-            core_verify_duration: core_verify_duration.as_secs_f64(),
-            core_proof_size,
-            compress_prove_duration: compress_duration.as_secs_f64(),
-            compress_verify_duration: recursive_verify_duration.as_secs_f64(),
-            compress_proof_size: recursive_proof_size,
-        }
+        let compressed_proof = setup
+            .prover
+            .compress(&ProverOpts::succinct(), receipt)
+            .map_err(|e| EvalError::Proving(e.to_string()))?;
+
+        // Get the recursive proof size, which has no seal in dev mode.
+        let seal = if setup.mock {
+            0
+        } else {
+            let succinct_receipt =
+                compressed_proof.inner.succinct().map_err(|e| EvalError::Proving(e.to_string()))?;
+            serialized_size(&succinct_receipt.seal)
+        };
+        let proof_size = ProofSize {
+            seal,
+            receipt: serialized_size(&compressed_proof),
+            public_values: compressed_proof.journal.bytes.len(),
+        };
+
+        self.compressed_receipt = Some(compressed_proof);
+        Ok(CompressedProofOutput { proof_size })
+    }
+
+    fn verify_compressed(&mut self) -> Result<()> {
+        let image_id = self.state().image_id;
+        let compressed_proof =
+            self.compressed_receipt.as_ref().expect("compress must run before verify_compressed");
+        compressed_proof.verify(image_id).map_err(|e| EvalError::Verification(e.to_string()))
+    }
+
+    fn tamper_core(&mut self) -> bool {
+        let receipt = self.receipt.as_ref().expect("prove_core must run before tamper_core");
+        self.rejects_tampered(receipt)
     }
 
-    #[cfg(not(feature = "risc0"))]
-    pub fn eval(_args: &EvalArgs) -> PerformanceReport {
-        panic!("RISC0 feature is not enabled. Please compile with --features risc0");
+    fn tamper_compressed(&mut self) -> bool {
+        let compressed_proof =
+            self.compressed_receipt.as_ref().expect("compress must run before tamper_compressed");
+        self.rejects_tampered(compressed_proof)
     }
-}
\ No newline at end of file
+}
@@ -1,14 +1,27 @@
-use std::fs;
-
 use crate::{
-    utils::{get_elf, get_reth_input, time_operation},
-    EvalArgs, PerformanceReport, ProgramId,
+    breakdown::ExecutionBreakdown,
+    error::{EvalError, Result},
+    evaluator::{
+        Capabilities, CompressedProofOutput, CoreProofOutput, Evaluator, ExecutionOutput,
+        HashSuite, Program,
+    },
+    proof_size::ProofSize,
+    tamper::{all_rejected, flip_bytes, tampered},
+    timeline::ShardTiming,
+    EvalArgs, HashFnId, Mode, ProverMode,
 };
 
 use sp1_core_executor::SP1Context;
 
-use sp1_prover::{components::DefaultProverComponents, utils::get_cycles, SP1Prover};
-use sp1_core_machine::io::SP1Stdin;
+use sp1_core_machine::{io::SP1Stdin, SP1_CIRCUIT_VERSION};
+use sp1_prover::{
+    components::DefaultProverComponents, SP1CoreProof, SP1CoreProofData, SP1ProvingKey,
+    SP1VerifyingKey,
+};
+use sp1_sdk::{
+    CpuProver, MockProver, ProofOpts, Prover, SP1Proof, SP1ProofKind, SP1ProofWithPublicValues,
+    SP1PublicValues,
+};
 
 #[cfg(feature = "cuda")]
 use sp1_cuda::SP1CudaProver;
@@ -16,99 +29,343 @@ use sp1_cuda::SP1CudaProver;
 #[cfg(not(feature = "cuda"))]
 use sp1_stark::SP1ProverOpts;
 
-pub struct SP1Evaluator;
+/// The state created during setup and shared by the later phases.
+struct SP1Setup {
+    /// The prover of the SDK, whose verifier also checks the public values against the proof.
+    prover: Box<dyn Prover<DefaultProverComponents>>,
+    #[cfg(feature = "cuda")]
+    server: SP1CudaProver,
+    elf: Vec<u8>,
+    stdin: SP1Stdin,
+    /// The proving and verifying keys, which are not generated in execute mode.
+    keys: Option<(SP1ProvingKey, SP1VerifyingKey)>,
+    shard_size: u64,
+    /// Whether the proofs are generated by the mock prover of the SDK.
+    mock: bool,
+}
+
+#[derive(Default)]
+pub struct SP1Evaluator {
+    setup: Option<SP1Setup>,
+    /// The core proof, with the public values it commits to.
+    core_proof: Option<SP1ProofWithPublicValues>,
+    /// The number of cycles of the last execution or core proof, which is handed back to the
+    /// prover to compress the core proof.
+    cycles: u64,
+    /// The compressed proof, with the public values it commits to.
+    compressed_proof: Option<SP1ProofWithPublicValues>,
+}
 
 impl SP1Evaluator {
-    pub fn eval(args: &EvalArgs) -> PerformanceReport {
-        // Setup the logger.
-        sp1_core_machine::utils::setup_logger();
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The options supported by SP1, which proves every phase with Poseidon2.
+    pub fn capabilities() -> Capabilities {
+        let poseidon = HashSuite { hashfn: HashFnId::Poseidon, name: "poseidon2", compress: true };
+        Capabilities { hashfns: vec![poseidon], shard_sizes: true, version: SP1_CIRCUIT_VERSION }
+    }
+
+    fn state(&self) -> &SP1Setup {
+        self.setup.as_ref().expect("setup must run before the other phases")
+    }
+
+    fn keys(&self) -> &(SP1ProvingKey, SP1VerifyingKey) {
+        self.state().keys.as_ref().expect("keys are only skipped in execute mode")
+    }
+
+    /// Returns the number of shards of an execution, derived from the shard size.
+    ///
+    /// This doesn't count the shards used for deferred precompiles.
+    fn shards(&self, cycles: u64) -> usize {
+        cycles.div_ceil(1 << self.state().shard_size) as usize
+    }
+
+    /// Generates a proof of the kind with the mock prover, which executes the program and returns
+    /// a mock proof of its public values.
+    fn prove_mock(&self, kind: SP1ProofKind) -> Result<SP1ProofWithPublicValues> {
+        let setup = self.state();
+        let (pk, _) = self.keys();
+        let opts = ProofOpts::default();
+        let context = SP1Context::default();
+        setup
+            .prover
+            .prove(pk, setup.stdin.clone(), opts, context, kind)
+            .map_err(|e| EvalError::Proving(e.to_string()))
+    }
+}
+
+impl Evaluator for SP1Evaluator {
+    fn setup(&mut self, args: &EvalArgs, program: &Program) -> Result<()> {
+        Self::capabilities().hash_suite(args).map_err(EvalError::Config)?;
 
         // Set enviroment variables to configure the prover.
         std::env::set_var("SHARD_SIZE", format!("{}", 1 << args.shard_size));
-        if args.program == ProgramId::Reth {
-            std::env::set_var("SHARD_CHUNKING_MULTIPLIER", "4");
-        }
 
         // Get stdin.
-        let stdin = if args.program == ProgramId::Reth {
-            let input = get_reth_input(args);
-            let mut stdin = SP1Stdin::new();
-            stdin.write(&input);
-            stdin
-        } else {
-            SP1Stdin::new()
-        };
-
-        // Get the elf.
-        let elf_path = get_elf(args);
-        let elf = fs::read(elf_path).unwrap();
-        let cycles = get_cycles(&elf, &stdin);
+        let mut stdin = SP1Stdin::new();
+        for input in &program.inputs {
+            stdin.write_vec(input.to_bytes()?);
+        }
 
-        let prover = SP1Prover::<DefaultProverComponents>::new();
+        let mock = args.prover_mode == ProverMode::Mock;
+        let prover: Box<dyn Prover<DefaultProverComponents>> =
+            if mock { Box::new(MockProver::new()) } else { Box::new(CpuProver::new()) };
 
         #[cfg(feature = "cuda")]
-        let server = SP1CudaProver::new().expect("Failed to initialize CUDA prover");
+        let server = SP1CudaProver::new()
+            .map_err(|e| EvalError::Config(format!("failed to initialize CUDA prover: {}", e)))?;
 
-        // Setup the program.
-        let (pk, vk) = prover.setup(&elf);
+        // Setup the program, unless it is only executed.
+        let keys = (args.mode != Mode::Execute).then(|| prover.setup(&program.elf));
 
-        // Execute the program.
+        self.setup = Some(SP1Setup {
+            prover,
+            #[cfg(feature = "cuda")]
+            server,
+            elf: program.elf.clone(),
+            stdin,
+            keys,
+            shard_size: args.shard_size,
+            mock,
+        });
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<ExecutionOutput> {
+        let setup = self.state();
         let context = SP1Context::default();
-        let (_, execution_duration) =
-            time_operation(|| prover.execute(&elf, &stdin, context.clone()));
+        let (_, report) = setup
+            .prover
+            .sp1_prover()
+            .execute(&setup.elf, &setup.stdin, context)
+            .map_err(|e| EvalError::Execution(e.to_string()))?;
+        let cycles = report.total_instruction_count();
+        self.cycles = cycles;
 
-        // Setup the prover opionts.
-        #[cfg(not(feature = "cuda"))]
-        let opts = SP1ProverOpts::default();
+        // The executor doesn't split the execution, so the number of shards is derived from the
+        // shard size.
+        let shards = self.shards(cycles);
+
+        let mut breakdown = ExecutionBreakdown {
+            touched_memory_addresses: Some(report.touched_memory_addresses),
+            cycle_tracker: report.cycle_tracker.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+            ..Default::default()
+        };
+        for (opcode, count) in report.opcode_counts.iter() {
+            breakdown.add_opcode(&format!("{:?}", opcode), *count);
+        }
+        for (syscall, count) in report.syscall_counts.iter() {
+            breakdown.add_syscall(&format!("{:?}", syscall), *count);
+        }
+
+        Ok(ExecutionOutput {
+            cycles,
+            shards,
+            syscalls: Some(report.total_syscall_count()),
+            breakdown,
+        })
+    }
+
+    fn prove_core(&mut self) -> Result<CoreProofOutput> {
+        if self.state().mock {
+            // The mock proof has no shards, so their number is derived from the execution.
+            let proof = self.prove_mock(SP1ProofKind::Core)?;
+            let output = CoreProofOutput {
+                shards: self.shards(self.cycles),
+                proof_size: proof_size(&proof),
+                shard_timings: Vec::new(),
+            };
+            self.core_proof = Some(proof);
+            return Ok(output);
+        }
+        let setup = self.state();
+        let (pk, _) = self.keys();
 
         // Generate the core proof (CPU).
         #[cfg(not(feature = "cuda"))]
-        let (core_proof, prove_core_duration) =
-            time_operation(|| prover.prove_core(&pk, &stdin, opts, context).unwrap());
+        let core_proof = {
+            let opts = SP1ProverOpts::default();
+            let context = SP1Context::default();
+            setup.prover.sp1_prover().prove_core(pk, &setup.stdin, opts, context)
+        };
 
         // Generate the core proof (CUDA).
         #[cfg(feature = "cuda")]
-        let (core_proof, prove_core_duration) =
-            time_operation(|| server.prove_core(&pk, &stdin).unwrap());
+        let core_proof = setup.server.prove_core(pk, &setup.stdin);
 
-        let num_shards = core_proof.proof.0.len();
+        let core_proof = core_proof.map_err(|e| EvalError::Proving(e.to_string()))?;
 
-        // Verify the proof.
-        let core_bytes = bincode::serialize(&core_proof).unwrap();
-        let (_, verify_core_duration) = time_operation(|| {
-            prover.verify(&core_proof.proof, &vk).expect("Proof verification failed")
-        });
+        // The height of the CPU trace of a shard is its number of cycles, padded to a power of
+        // two. Shards of deferred precompiles don't have a CPU trace.
+        let shard_timings = core_proof
+            .proof
+            .0
+            .iter()
+            .enumerate()
+            .map(|(index, shard)| ShardTiming {
+                index: index as u64,
+                padded_cycles: shard
+                    .chip_ordering
+                    .get("CPU")
+                    .map(|&chip| 1 << shard.opened_values.chips[chip].log_degree),
+                ..Default::default()
+            })
+            .collect();
+        let shards = core_proof.proof.0.len();
+        let proof = SP1ProofWithPublicValues {
+            proof: SP1Proof::Core(core_proof.proof.0),
+            stdin: core_proof.stdin,
+            public_values: core_proof.public_values,
+            sp1_version: setup.prover.version().to_string(),
+        };
+        let output = CoreProofOutput { shards, proof_size: proof_size(&proof), shard_timings };
+        self.cycles = core_proof.cycles;
+        self.core_proof = Some(proof);
+        Ok(output)
+    }
+
+    fn verify_core(&mut self) -> Result<()> {
+        let setup = self.state();
+        let (_, vk) = self.keys();
+        let core_proof = self.core_proof.as_ref().expect("prove_core must run before verify_core");
+        setup.prover.verify(core_proof, vk).map_err(|e| EvalError::Verification(e.to_string()))
+    }
+
+    fn compress(&mut self) -> Result<CompressedProofOutput> {
+        if self.state().mock {
+            let proof = self.prove_mock(SP1ProofKind::Compressed)?;
+            let proof_size = proof_size(&proof);
+            self.compressed_proof = Some(proof);
+            return Ok(CompressedProofOutput { proof_size });
+        }
+        let core = self.core_proof.take().expect("prove_core must run before compress");
+        let SP1Proof::Core(shards) = core.proof else {
+            unreachable!("prove_core generates core proofs")
+        };
+        let core_proof = SP1CoreProof {
+            proof: SP1CoreProofData(shards),
+            stdin: core.stdin.clone(),
+            public_values: core.public_values.clone(),
+            cycles: self.cycles,
+        };
+        let setup = self.state();
+        let (_, vk) = self.keys();
 
         #[cfg(not(feature = "cuda"))]
-        let (compress_proof, compress_duration) =
-            time_operation(|| prover.compress(&vk, core_proof, vec![], opts).unwrap());
+        let compress_proof = {
+            let opts = SP1ProverOpts::default();
+            setup.prover.sp1_prover().compress(vk, core_proof, vec![], opts)
+        };
 
         #[cfg(feature = "cuda")]
-        let (compress_proof, compress_duration) =
-            time_operation(|| server.compress(&vk, core_proof, vec![]).unwrap());
+        let compress_proof = setup.server.compress(vk, core_proof, vec![]);
 
-        let compress_bytes = bincode::serialize(&compress_proof).unwrap();
-        println!("recursive proof size: {}", compress_bytes.len());
+        let compress_proof = compress_proof.map_err(|e| EvalError::Proving(e.to_string()))?;
+        let proof = SP1ProofWithPublicValues {
+            proof: SP1Proof::Compressed(Box::new(compress_proof)),
+            ..core
+        };
+        let proof_size = proof_size(&proof);
+        println!("recursive proof size: {}", proof_size.receipt);
 
-        let prove_duration = prove_core_duration + compress_duration;
+        self.compressed_proof = Some(proof);
+        Ok(CompressedProofOutput { proof_size })
+    }
 
-        // Create the performance report.
-        PerformanceReport {
-            program: args.program.to_string(),
-            prover: args.prover.to_string(),
-            hashfn: args.hashfn.to_string(),
-            shard_size: args.shard_size,
-            shards: num_shards,
-            cycles: cycles as u64,
-            speed: (cycles as f64) / prove_core_duration.as_secs_f64(),
-            execution_duration: execution_duration.as_secs_f64(),
-            prove_duration: prove_duration.as_secs_f64(),
-            core_prove_duration: prove_core_duration.as_secs_f64(),
-            core_verify_duration: verify_core_duration.as_secs_f64(),
-            core_proof_size: core_bytes.len(),
-            compress_prove_duration: compress_duration.as_secs_f64(),
-            compress_verify_duration: 0.0, // TODO: fill this in.
-            compress_proof_size: compress_bytes.len(),
+    fn verify_compressed(&mut self) -> Result<()> {
+        let setup = self.state();
+        let (_, vk) = self.keys();
+        let compressed_proof =
+            self.compressed_proof.as_ref().expect("compress must run before verify_compressed");
+        setup
+            .prover
+            .verify(compressed_proof, vk)
+            .map_err(|e| EvalError::Verification(e.to_string()))
+    }
+
+    fn tamper_core(&mut self) -> bool {
+        let setup = self.state();
+        let (_, vk) = self.keys();
+        let core_proof = self.core_proof.as_ref().expect("prove_core must run before tamper_core");
+        let SP1Proof::Core(shards) = &core_proof.proof else {
+            unreachable!("prove_core generates core proofs")
+        };
+
+        // Flip bytes in the shard proofs, and in the public values committed by the first shard.
+        let mut proofs = tampered(shards);
+        if let Some(shard) = shards.first() {
+            for public_values in tampered(&shard.public_values) {
+                let mut proof = shards.clone();
+                proof[0].public_values = public_values;
+                proofs.push(proof);
+            }
         }
+        let proofs: Vec<_> =
+            proofs.into_iter().map(|proof| with_proof(core_proof, SP1Proof::Core(proof))).collect();
+
+        let verify = |proof: &SP1ProofWithPublicValues| setup.prover.verify(proof, vk);
+        all_rejected(&proofs, verify) && all_rejected(&tampered_public_values(core_proof), verify)
+    }
+
+    fn tamper_compressed(&mut self) -> bool {
+        let setup = self.state();
+        let (_, vk) = self.keys();
+        let compressed_proof =
+            self.compressed_proof.as_ref().expect("compress must run before tamper_compressed");
+        let SP1Proof::Compressed(compressed) = &compressed_proof.proof else {
+            unreachable!("compress generates compressed proofs")
+        };
+
+        // Flip bytes in the proof, and in the public values committed by its shard.
+        let mut proofs = tampered(compressed.as_ref());
+        for public_values in tampered(&compressed.proof.public_values) {
+            let mut proof = compressed.as_ref().clone();
+            proof.proof.public_values = public_values;
+            proofs.push(proof);
+        }
+        let proofs: Vec<_> = proofs
+            .into_iter()
+            .map(|proof| with_proof(compressed_proof, SP1Proof::Compressed(Box::new(proof))))
+            .collect();
+
+        let verify = |proof: &SP1ProofWithPublicValues| setup.prover.verify(proof, vk);
+        all_rejected(&proofs, verify)
+            && all_rejected(&tampered_public_values(compressed_proof), verify)
     }
 }
+
+/// Measures a proof, whose public values are kept apart from it.
+fn proof_size(proof: &SP1ProofWithPublicValues) -> ProofSize {
+    let public_values = proof.public_values.as_slice();
+    match &proof.proof {
+        SP1Proof::Core(shards) => ProofSize::with_public_values(shards, public_values),
+        SP1Proof::Compressed(proof) => ProofSize::with_public_values(&proof.proof, public_values),
+        _ => unreachable!("only core and compressed proofs are generated"),
+    }
+}
+
+/// Returns a copy of a proof with the proof itself replaced, keeping its public values.
+fn with_proof(original: &SP1ProofWithPublicValues, proof: SP1Proof) -> SP1ProofWithPublicValues {
+    SP1ProofWithPublicValues {
+        proof,
+        stdin: original.stdin.clone(),
+        public_values: original.public_values.clone(),
+        sp1_version: original.sp1_version.clone(),
+    }
+}
+
+/// Returns copies of a proof with a byte of the public values read by the user flipped, which the
+/// verifier checks against the digest committed by the proof.
+fn tampered_public_values(original: &SP1ProofWithPublicValues) -> Vec<SP1ProofWithPublicValues> {
+    flip_bytes(original.public_values.as_slice())
+        .iter()
+        .map(|public_values| SP1ProofWithPublicValues {
+            proof: original.proof.clone(),
+            stdin: original.stdin.clone(),
+            public_values: SP1PublicValues::from(public_values),
+            sp1_version: original.sp1_version.clone(),
+        })
+        .collect()
+}
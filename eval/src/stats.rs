@@ -0,0 +1,71 @@
+/// Summary statistics over the samples of a measurement.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub mean: f64,
+    pub median: f64,
+    /// The sample standard deviation, or zero with fewer than two samples.
+    pub stddev: f64,
+    pub min: f64,
+    pub max: f64,
+    /// The 95th percentile, using the nearest-rank method.
+    pub p95: f64,
+}
+
+impl Summary {
+    /// Computes the summary of the given samples, or all zeros if there are none.
+    pub fn of(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let n = sorted.len();
+
+        let mean = sorted.iter().sum::<f64>() / n as f64;
+        let median =
+            if n % 2 == 0 { (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0 } else { sorted[n / 2] };
+        let stddev = if n < 2 {
+            0.0
+        } else {
+            let variance =
+                sorted.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1) as f64;
+            variance.sqrt()
+        };
+        let rank = ((0.95 * n as f64).ceil() as usize).max(1);
+
+        Self { mean, median, stddev, min: sorted[0], max: sorted[n - 1], p95: sorted[rank - 1] }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_of_no_samples_is_zero() {
+        assert_eq!(Summary::of(&[]), Summary::default());
+    }
+
+    #[test]
+    fn summary_of_single_sample() {
+        let summary = Summary::of(&[2.5]);
+        assert_eq!(summary.mean, 2.5);
+        assert_eq!(summary.median, 2.5);
+        assert_eq!(summary.stddev, 0.0);
+        assert_eq!(summary.min, 2.5);
+        assert_eq!(summary.max, 2.5);
+        assert_eq!(summary.p95, 2.5);
+    }
+
+    #[test]
+    fn summary_of_unsorted_samples() {
+        let summary = Summary::of(&[4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(summary.mean, 2.5);
+        assert_eq!(summary.median, 2.5);
+        assert!((summary.stddev - 1.290_994_448_735_805_6).abs() < 1e-12);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 4.0);
+        assert_eq!(summary.p95, 4.0);
+    }
+}
@@ -0,0 +1,548 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use clap::ValueEnum;
+use serde::{de::Error as _, Deserialize, Deserializer};
+
+use crate::{
+    blocks::{select_blocks, BlockSelector},
+    error::{EvalError, Result},
+    evaluator::Registry,
+    harness::{self, result_stem},
+    isolation::{evaluate_isolated, Isolated, Limits},
+    ledger::{config_hash, Ledger, LedgerEntry},
+    programs::{GuestSpec, InputProvider, ProgramRegistry},
+    EvalArgs, HashFnId, Mode, OutputFormat, ProverId, ProverMode,
+};
+
+/// The arguments of a sweep.
+#[derive(clap::Args, Clone)]
+pub struct SweepArgs {
+    /// The TOML file describing the matrix of evaluations.
+    #[arg(long, default_value = "sweep.toml")]
+    config: PathBuf,
+    /// Start a new ledger, running every evaluation again instead of resuming the sweep.
+    #[arg(long)]
+    fresh: bool,
+}
+
+/// A matrix of evaluations, read from a TOML file.
+///
+/// Every combination of the programs, provers, hash functions and shard sizes is evaluated, and
+/// programs that take a block or fixture as input are evaluated on each of the blocks or fixtures.
+/// The values are named as on the command line, e.g. `poseidon` or `sp1`, and programs as in the
+/// program registry. Blocks are selected by number, or by label or gas band from the manifest of
+/// the block inputs.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SweepConfig {
+    /// The name of the result files every evaluation is appended to.
+    filename: String,
+    /// The number of measured trials of each evaluation.
+    #[serde(default = "default_trials")]
+    trials: usize,
+    /// The number of warmup trials of each evaluation.
+    #[serde(default)]
+    warmup: usize,
+    /// How far through the proving pipeline each evaluation runs.
+    #[serde(default = "default_mode", deserialize_with = "value")]
+    mode: Mode,
+    /// Whether each evaluation generates real proofs or mock ones.
+    #[serde(default = "default_prover_mode", deserialize_with = "value")]
+    prover_mode: ProverMode,
+    /// The format of the result files.
+    #[serde(default = "default_format", deserialize_with = "value")]
+    format: OutputFormat,
+    /// The number of times a failed trial is retried when the sweep is resumed.
+    #[serde(default = "default_retries")]
+    retries: usize,
+    /// The wall-clock time in seconds after which an evaluation is killed.
+    #[serde(default)]
+    timeout: Option<u64>,
+    /// The size of the address space of an evaluation in GiB, past which its allocations fail.
+    #[serde(default)]
+    memory_limit: Option<u64>,
+    /// Whether to check that the verifiers reject tampered proofs.
+    #[serde(default)]
+    tamper_check: bool,
+    /// Whether to record the timelines of the core proofs.
+    #[serde(default)]
+    timeline: bool,
+    /// Whether to use the ELFs of the last builds instead of building the programs.
+    #[serde(default)]
+    skip_build: bool,
+    /// The programs to evaluate.
+    programs: Vec<String>,
+    /// The provers to evaluate.
+    #[serde(deserialize_with = "values")]
+    provers: Vec<ProverId>,
+    /// The hash functions to evaluate.
+    #[serde(deserialize_with = "values")]
+    hashfns: Vec<HashFnId>,
+    /// The log2 shard sizes to evaluate.
+    shard_sizes: Vec<u64>,
+    /// The blocks the programs that take a block as input are evaluated on.
+    #[serde(default)]
+    blocks: Vec<BlockSelector>,
+    /// The fixtures the Fuel programs are evaluated on.
+    #[serde(default)]
+    fixtures: Vec<String>,
+    /// The input files written to every program after its own inputs.
+    #[serde(default)]
+    inputs: Vec<PathBuf>,
+}
+
+/// A single evaluation of a sweep.
+pub struct Combination {
+    /// The arguments the evaluation runs with.
+    pub args: EvalArgs,
+    /// The reason the evaluation is skipped, if it can't run in this build.
+    pub skip: Option<String>,
+}
+
+fn default_trials() -> usize {
+    1
+}
+
+fn default_retries() -> usize {
+    2
+}
+
+fn default_mode() -> Mode {
+    Mode::Full
+}
+
+fn default_prover_mode() -> ProverMode {
+    ProverMode::Real
+}
+
+fn default_format() -> OutputFormat {
+    OutputFormat::Csv
+}
+
+/// Deserializes a value by its command line name.
+fn value<'de, D: Deserializer<'de>, T: ValueEnum>(
+    deserializer: D,
+) -> std::result::Result<T, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    T::from_str(&name, true).map_err(D::Error::custom)
+}
+
+/// Deserializes a list of values by their command line names.
+fn values<'de, D: Deserializer<'de>, T: ValueEnum>(
+    deserializer: D,
+) -> std::result::Result<Vec<T>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|name| T::from_str(name, true).map_err(D::Error::custom))
+        .collect()
+}
+
+impl SweepConfig {
+    /// Reads and validates the sweep config at the path.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            EvalError::Config(format!("failed to read sweep config {}: {}", path.display(), e))
+        })?;
+        Self::parse(&contents).map_err(|e| {
+            EvalError::Config(format!("invalid sweep config {}: {}", path.display(), e))
+        })
+    }
+
+    /// Parses and validates a sweep config.
+    fn parse(contents: &str) -> std::result::Result<Self, String> {
+        let config: Self = toml::from_str(contents).map_err(|e| e.to_string())?;
+        if config.trials == 0 {
+            return Err("at least one trial is required".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Expands the matrix into its evaluations, in the order they run.
+    ///
+    /// Combinations the registered evaluators or the program's guests can't run are kept, but
+    /// marked as skipped. Unknown programs, and programs that take a block or fixture when none is
+    /// configured, are configuration errors.
+    pub fn combinations(
+        &self,
+        registry: &Registry,
+        programs: &ProgramRegistry,
+    ) -> Result<Vec<Combination>> {
+        let mut combinations = Vec::new();
+        for program in &self.programs {
+            let spec = programs.get(program)?;
+            let inputs = match spec.input {
+                InputProvider::None | InputProvider::Sha2Chain(_) => vec![(None, None)],
+                InputProvider::Block if self.blocks.is_empty() => {
+                    return Err(EvalError::Config(format!(
+                        "the {} program requires at least one block",
+                        program
+                    )));
+                }
+                InputProvider::Block => select_blocks(&self.blocks)?
+                    .into_iter()
+                    .map(|block| (Some(block), None))
+                    .collect(),
+                InputProvider::Fixture(_) if self.fixtures.is_empty() => {
+                    return Err(EvalError::Config(format!(
+                        "the {} program requires at least one fixture",
+                        program
+                    )));
+                }
+                InputProvider::Fixture(_) => {
+                    self.fixtures.iter().map(|fixture| (None, Some(fixture.clone()))).collect()
+                }
+            };
+            for (block_number, fixture) in inputs {
+                for &prover in &self.provers {
+                    for hashfn in &self.hashfns {
+                        for (index, &shard_size) in self.shard_sizes.iter().enumerate() {
+                            let args = EvalArgs {
+                                program: program.clone(),
+                                prover,
+                                hashfn: hashfn.clone(),
+                                shard_size,
+                                filename: self.filename.clone(),
+                                block_number,
+                                fixture: fixture.clone(),
+                                inputs: self.inputs.clone(),
+                                trials: self.trials,
+                                warmup: self.warmup,
+                                mode: self.mode,
+                                prover_mode: self.prover_mode,
+                                tamper_check: self.tamper_check,
+                                timeline: self.timeline,
+                                skip_build: self.skip_build,
+                                format: self.format,
+                            };
+                            let skip = match spec.guest(prover) {
+                                Some(guest) => skip_reason(registry, &args, guest, index),
+                                None => Some(format!(
+                                    "{} has no guest for {}",
+                                    program,
+                                    prover.to_string()
+                                )),
+                            };
+                            combinations.push(Combination { args, skip });
+                        }
+                    }
+                }
+            }
+        }
+        Ok(combinations)
+    }
+}
+
+/// Returns why the evaluation can't run with the registered evaluators, if it can't.
+///
+/// The index is the position of the shard size in the sweep.
+fn skip_reason(
+    registry: &Registry,
+    args: &EvalArgs,
+    guest: &GuestSpec,
+    index: usize,
+) -> Option<String> {
+    let prover = args.prover.to_string();
+    let Some(capabilities) = registry.capabilities(args.prover) else {
+        return Some(format!(
+            "{} isn't enabled in this build, compile with --features {}",
+            prover, prover
+        ));
+    };
+    if let Err(reason) = capabilities.hash_suite(args) {
+        return Some(reason);
+    }
+    if let Err(reason) = capabilities.check_version(args, guest) {
+        return Some(reason);
+    }
+    if !capabilities.shard_sizes && index > 0 {
+        return Some(format!("{} only runs the first shard size", prover));
+    }
+    None
+}
+
+/// Describes the evaluation in progress messages.
+fn describe(args: &EvalArgs) -> String {
+    let mut description = format!(
+        "{}, {}, {}, {}",
+        args.program,
+        args.prover.to_string(),
+        args.hashfn.to_string(),
+        args.shard_size
+    );
+    if let Some(block_number) = args.block_number {
+        description += &format!(", block {}", block_number);
+    }
+    if let Some(fixture) = &args.fixture {
+        description += &format!(", fixture {}", fixture);
+    }
+    description
+}
+
+/// Runs every evaluation of the sweep in its own process, appending them to the same result files.
+///
+/// An evaluation that times out, runs out of memory or crashes takes its process down but not the
+/// sweep, which records the failure in a row of its own and moves on.
+///
+/// The trials that ran are recorded in a ledger next to the result files. When the sweep is run
+/// again, it skips the trials that succeeded, resumes configurations from their first unfinished
+/// trial and retries the failed ones until they failed more than `retries` times.
+///
+/// A failed evaluation doesn't stop the sweep. The error of the first failure is returned once
+/// every evaluation has run, or a configuration error if configurations were given up on.
+pub fn run(
+    args: &SweepArgs,
+    registry: &Registry,
+    programs: &ProgramRegistry,
+    results_dir: &Path,
+) -> Result<()> {
+    let config = SweepConfig::load(&args.config)?;
+    let combinations = config.combinations(registry, programs)?;
+
+    let ledger_path = Ledger::path(results_dir, &result_stem(&config.filename));
+    if args.fresh && ledger_path.exists() {
+        fs::remove_file(&ledger_path).map_err(|e| {
+            EvalError::Config(format!("failed to remove ledger {}: {}", ledger_path.display(), e))
+        })?;
+    }
+    let mut ledger = Ledger::load(&ledger_path)?;
+    let limits = Limits {
+        timeout: config.timeout.map(Duration::from_secs),
+        memory: config.memory_limit.map(|gib| gib << 30),
+    };
+    println!("Ledger: {}", ledger_path.display());
+
+    let (mut succeeded, mut failed, mut skipped, mut done) = (0, 0, 0, 0);
+    let mut given_up = Vec::new();
+    let mut first_error = None;
+    for combination in combinations {
+        let description = describe(&combination.args);
+        if let Some(reason) = combination.skip {
+            println!("Skipping: {} ({})", description, reason);
+            skipped += 1;
+            continue;
+        }
+
+        let config_hash = config_hash(&combination.args);
+        let progress = ledger.progress(&config_hash);
+        if progress.completed >= combination.args.trials {
+            println!("Done: {}", description);
+            done += 1;
+            continue;
+        }
+        if progress.failures > config.retries {
+            println!("Giving up: {} (failed {} times)", description, progress.failures);
+            given_up.push(description);
+            continue;
+        }
+
+        // Only run the trials that haven't succeeded yet.
+        let mut eval_args = combination.args;
+        eval_args.trials -= progress.completed;
+        match (progress.completed, progress.failures) {
+            (0, 0) => println!("Running: {}", description),
+            (0, failures) => println!("Retrying: {} (failed {} times)", description, failures),
+            (completed, _) => println!("Resuming: {} from trial {}", description, completed),
+        }
+
+        let Isolated { recorded_trials, result } = evaluate_isolated(&eval_args, limits);
+        let result = result.or_else(|err| match err {
+            EvalError::Timeout(_) | EvalError::OutOfMemory(_) | EvalError::Crash(_) => {
+                harness::record_failure(&eval_args, registry, results_dir, &err)?;
+                Err(err)
+            }
+            err => Err(err),
+        });
+
+        // The trials whose rows were written are done, even if a later one failed, so that they
+        // aren't written again when the sweep resumes.
+        let completed = match result {
+            Ok(()) => eval_args.trials,
+            Err(_) => recorded_trials.min(eval_args.trials),
+        };
+        for trial in progress.completed..progress.completed + completed {
+            let status = "ok".to_string();
+            let entry = LedgerEntry { config: config_hash.clone(), trial, status, error: None };
+            ledger.record(entry)?;
+        }
+        match result {
+            Ok(()) => succeeded += 1,
+            Err(err) => {
+                eprintln!("Failed: {}: {}", description, err);
+                ledger.record(LedgerEntry {
+                    config: config_hash,
+                    trial: progress.completed + completed,
+                    status: err.status().to_string(),
+                    error: Some(err.to_string()),
+                })?;
+                failed += 1;
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+
+    println!(
+        "Sweep finished: {} succeeded, {} failed, {} skipped, {} already done, {} given up",
+        succeeded,
+        failed,
+        skipped,
+        done,
+        given_up.len()
+    );
+    if failed > 0 {
+        println!("{} configurations remain, run the sweep again to retry them", failed);
+    }
+    match first_error {
+        Some(err) => Err(err),
+        None if !given_up.is_empty() => Err(EvalError::Config(format!(
+            "gave up on configurations that failed more than {} retries: {}",
+            config.retries,
+            given_up.join("; ")
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        evaluator::{Capabilities, HashSuite},
+        sp1::SP1Evaluator,
+    };
+
+    const CONFIG: &str = r#"
+        filename = "benchmark"
+        programs = ["loop", "reth"]
+        provers = ["sp1", "risc0"]
+        hashfns = ["poseidon", "sha256"]
+        shard_sizes = [21, 22]
+        blocks = [17106222, 19409768]
+    "#;
+
+    const PROGRAMS: &str = r#"
+        [loop]
+        sp1 = { dir = "loop", elf = "loop" }
+        risc0 = { dir = "loop", elf = "loop" }
+
+        [reth]
+        input = "block"
+        sp1 = { dir = "reth-sp1", elf = "reth-sp1" }
+
+        [fuel]
+        input = { fixture = "decompression" }
+        sp1 = { dir = "fuel-sp1", elf = "fuel" }
+
+        [pinned]
+        sp1 = { dir = "pinned", elf = "pinned", zkvm_version = "0.0.1" }
+    "#;
+
+    fn registry() -> Registry {
+        let mut registry = Registry::new();
+        registry.register(ProverId::SP1, SP1Evaluator::capabilities(), || {
+            Box::new(SP1Evaluator::new())
+        });
+        registry
+    }
+
+    fn programs() -> ProgramRegistry {
+        ProgramRegistry::parse(PathBuf::new(), PROGRAMS).unwrap()
+    }
+
+    #[test]
+    fn combinations_are_checked_against_capabilities() {
+        let config = SweepConfig::parse(CONFIG).unwrap();
+        let combinations = config.combinations(&registry(), &programs()).unwrap();
+
+        // loop and two reth blocks, each with two provers, hash functions and shard sizes.
+        assert_eq!(combinations.len(), 3 * 2 * 2 * 2);
+        let runnable: Vec<_> = combinations.iter().filter(|c| c.skip.is_none()).collect();
+        assert_eq!(runnable.len(), 3 * 2);
+        assert!(runnable.iter().all(|c| c.args.prover == ProverId::SP1));
+        assert!(runnable.iter().all(|c| c.args.hashfn == HashFnId::Poseidon));
+        assert_eq!(runnable[1].args.block_number, None);
+        assert_eq!(runnable[2].args.block_number, Some(17106222));
+
+        let reth_risc0 = combinations
+            .iter()
+            .find(|c| c.args.program == "reth" && c.args.prover == ProverId::Risc0)
+            .unwrap();
+        assert_eq!(reth_risc0.skip.as_deref(), Some("reth has no guest for risc0"));
+    }
+
+    #[test]
+    fn invalid_configs_are_rejected() {
+        assert!(SweepConfig::parse(&CONFIG.replace("\"sp1\"", "\"sp2\"")).is_err());
+        assert!(SweepConfig::parse(&format!("{}\ntrials = 0", CONFIG)).is_err());
+        assert!(SweepConfig::parse(&format!("{}\nunknown = 1", CONFIG)).is_err());
+
+        let without_blocks = SweepConfig::parse(&CONFIG.replace("blocks", "# blocks")).unwrap();
+        assert!(without_blocks.combinations(&registry(), &programs()).is_err());
+        let unknown = SweepConfig::parse(&CONFIG.replace("\"loop\"", "\"lop\"")).unwrap();
+        assert!(unknown.combinations(&registry(), &programs()).is_err());
+    }
+
+    #[test]
+    fn fixture_programs_run_on_each_fixture() {
+        let source = CONFIG.replace("\"loop\", \"reth\"", "\"fuel\"");
+        let without_fixtures = SweepConfig::parse(&source).unwrap();
+        assert!(without_fixtures.combinations(&registry(), &programs()).is_err());
+
+        let config = SweepConfig::parse(&format!("{}\nfixtures = [\"a\", \"b\"]", source)).unwrap();
+        let combinations = config.combinations(&registry(), &programs()).unwrap();
+        let fixtures: Vec<_> = combinations
+            .iter()
+            .filter(|c| c.skip.is_none())
+            .map(|c| (c.args.fixture.as_deref(), c.args.block_number))
+            .collect();
+        // Each fixture with both shard sizes.
+        assert_eq!(
+            fixtures,
+            [(Some("a"), None), (Some("a"), None), (Some("b"), None), (Some("b"), None)]
+        );
+    }
+
+    #[test]
+    fn hash_suites_that_cant_be_compressed_only_run_core() {
+        let mut registry = registry();
+        let suite = |hashfn, name, compress| HashSuite { hashfn, name, compress };
+        let capabilities = Capabilities {
+            hashfns: vec![
+                suite(HashFnId::Poseidon, "poseidon2", true),
+                suite(HashFnId::Sha256, "sha-256", false),
+            ],
+            shard_sizes: false,
+            version: "1.1.3",
+        };
+        registry.register(ProverId::Risc0, capabilities, || Box::new(SP1Evaluator::new()));
+
+        let source = CONFIG.replace("\"loop\", \"reth\"", "\"loop\"");
+        let config = SweepConfig::parse(&source).unwrap();
+        let runnable = |config: &SweepConfig| {
+            let combinations = config.combinations(&registry, &programs()).unwrap();
+            combinations
+                .into_iter()
+                .filter(|c| c.skip.is_none() && c.args.prover == ProverId::Risc0)
+                .map(|c| c.args.hashfn.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(runnable(&config), ["poseidon"]);
+        let core = SweepConfig::parse(&format!("{}\nmode = \"core\"", source)).unwrap();
+        assert_eq!(runnable(&core), ["poseidon", "sha-256"]);
+    }
+
+    #[test]
+    fn guests_pinned_to_another_version_are_skipped() {
+        let config =
+            SweepConfig::parse(&CONFIG.replace("\"loop\", \"reth\"", "\"pinned\"")).unwrap();
+        let combinations = config.combinations(&registry(), &programs()).unwrap();
+        let sp1 = combinations
+            .iter()
+            .find(|c| c.args.prover == ProverId::SP1 && c.args.hashfn == HashFnId::Poseidon)
+            .unwrap();
+        assert!(sp1.skip.as_deref().unwrap().contains("built against sp1 0.0.1"));
+    }
+}
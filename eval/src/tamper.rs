@@ -0,0 +1,119 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The byte offsets flipped in a tampered copy, as fractions of the length of the bytes.
+const FLIP_OFFSETS: [f64; 3] = [0.25, 0.5, 0.75];
+
+/// The number of bytes tried from a flip offset until a tampered copy of a value decodes.
+const MAX_FLIP_ATTEMPTS: usize = 64;
+
+/// Returns the index of the byte flipped at an offset, given the length of the bytes.
+fn flip_index(len: usize, fraction: f64) -> usize {
+    ((len as f64 * fraction) as usize).min(len - 1)
+}
+
+/// Returns copies of the bytes with a single byte flipped, one copy per flip offset.
+///
+/// Empty bytes have nothing to flip, so a single copy with one byte appended is returned instead.
+pub fn flip_bytes(bytes: &[u8]) -> Vec<Vec<u8>> {
+    if bytes.is_empty() {
+        return vec![vec![0]];
+    }
+
+    FLIP_OFFSETS
+        .iter()
+        .map(|fraction| {
+            let mut tampered = bytes.to_vec();
+            tampered[flip_index(bytes.len(), *fraction)] ^= 0xff;
+            tampered
+        })
+        .collect()
+}
+
+/// Returns copies of the value with a single byte of its bincode encoding flipped, one copy per
+/// flip offset.
+///
+/// A copy whose encoding no longer decodes can't be passed to a verifier, so the bytes following
+/// the offset are flipped in its place until one decodes. An offset is left without a copy only if
+/// none of them do.
+pub fn tampered<T: Serialize + DeserializeOwned>(value: &T) -> Vec<T> {
+    let mut bytes = bincode::serialize(value).unwrap();
+    if bytes.is_empty() {
+        return Vec::new();
+    }
+
+    let mut copies = Vec::new();
+    for fraction in FLIP_OFFSETS {
+        let start = flip_index(bytes.len(), fraction);
+        for index in (start..bytes.len()).take(MAX_FLIP_ATTEMPTS) {
+            bytes[index] ^= 0xff;
+            let copy = bincode::deserialize(&bytes);
+            bytes[index] ^= 0xff;
+            if let Ok(copy) = copy {
+                copies.push(copy);
+                break;
+            }
+        }
+    }
+    copies
+}
+
+/// Returns whether the verification of a tampered proof fails.
+///
+/// Verifiers may panic rather than return an error on malformed proofs, so a panic also counts as a
+/// rejection.
+pub fn is_rejected<E>(verify: impl FnOnce() -> Result<(), E>) -> bool {
+    !matches!(panic::catch_unwind(AssertUnwindSafe(verify)), Ok(Ok(())))
+}
+
+/// Returns whether there are tampered copies and every one of them fails to verify.
+///
+/// A check without copies verified nothing, so it fails instead of passing vacuously.
+pub fn all_rejected<T, E>(copies: &[T], verify: impl Fn(&T) -> Result<(), E>) -> bool {
+    !copies.is_empty() && copies.iter().all(|copy| is_rejected(|| verify(copy)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flipped_copies_differ_in_one_byte() {
+        let bytes = [1, 2, 3, 4, 5, 6, 7, 8];
+        let copies = flip_bytes(&bytes);
+        assert_eq!(copies.len(), FLIP_OFFSETS.len());
+        for copy in copies {
+            assert_eq!(copy.len(), bytes.len());
+            assert_eq!(copy.iter().zip(&bytes).filter(|(a, b)| a != b).count(), 1);
+        }
+        assert_eq!(flip_bytes(&[]), vec![vec![0]]);
+    }
+
+    #[test]
+    fn undecodable_copies_flip_the_following_bytes() {
+        // Flipped bools and lengths don't decode, so the first half only yields copies once the
+        // flips reach the bytes of the second vector.
+        let value = (vec![true; 8], vec![0u8; 24]);
+        let copies = tampered(&value);
+        assert_eq!(copies.len(), FLIP_OFFSETS.len());
+        for copy in copies {
+            assert_eq!(copy.0, value.0);
+            assert_eq!(copy.1.iter().filter(|byte| **byte != 0).count(), 1);
+        }
+    }
+
+    #[test]
+    fn verification_panics_are_rejections() {
+        assert!(!is_rejected(|| Ok::<(), ()>(())));
+        assert!(is_rejected(|| Err(())));
+        assert!(is_rejected(|| -> Result<(), ()> { panic!("malformed proof") }));
+    }
+
+    #[test]
+    fn checks_without_copies_fail() {
+        assert!(!all_rejected(&[] as &[u8], |_| Err::<(), ()>(())));
+        assert!(all_rejected(&[1u8], |_| Err::<(), ()>(())));
+        assert!(!all_rejected(&[1u8, 2], |copy| if *copy == 1 { Err(()) } else { Ok(()) }));
+    }
+}
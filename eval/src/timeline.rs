@@ -0,0 +1,362 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, OnceLock},
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{
+    field::{Field, Visit},
+    span, Subscriber,
+};
+use tracing_subscriber::{
+    filter::LevelFilter, layer::Context, prelude::*, registry::LookupSpan, reload, EnvFilter, Layer,
+};
+
+/// The fields of a span that hold the index of the shard or segment it works on.
+const INDEX_FIELDS: [&str; 10] = [
+    "shard",
+    "shard_index",
+    "shard_idx",
+    "shard_id",
+    "segment",
+    "segment_index",
+    "index",
+    "idx",
+    "chunk",
+    "checkpoint_idx",
+];
+
+/// The spans closed while recording, if spans are being recorded.
+static RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+
+/// Sets the level up to which the spans of the provers are timed, once the logger is installed.
+static RECORDER_LEVEL: OnceLock<Box<dyn Fn(LevelFilter) + Send + Sync>> = OnceLock::new();
+
+/// The spans closed since the recording started.
+struct Recording {
+    /// When the recording started, which the spans are timed from.
+    start: Instant,
+    /// The spans closed so far.
+    spans: Vec<SpanRecord>,
+}
+
+/// The stage of proving a shard that a span is part of, judged by its name.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    /// Generating the traces of the shard.
+    TraceGeneration,
+    /// Committing to the traces.
+    Commit,
+    /// Proving the shard.
+    Proving,
+}
+
+/// A span of the prover, timed from the start of the recording.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpanRecord {
+    /// The name of the span.
+    pub name: String,
+    /// The index of the shard or segment the span (or one of its parents) works on.
+    pub index: Option<u64>,
+    /// The stage the span is counted in, unless a parent working on the same shard is counted in it
+    /// already.
+    pub stage: Option<Stage>,
+    /// When the span was created in seconds.
+    pub start: f64,
+    /// How long the span was open in seconds.
+    pub duration: f64,
+}
+
+/// How long a shard or segment of a proof took.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ShardTiming {
+    /// The index of the shard or segment, as the prover numbers them.
+    pub index: u64,
+    /// The number of cycles of the shard, padded to the height of its trace, which is what the
+    /// prover pays for rather than the cycles the shard executed.
+    pub padded_cycles: Option<u64>,
+    /// When the first work on the shard started in seconds from the start of the proof.
+    pub start: Option<f64>,
+    /// When the last work on the shard ended in seconds from the start of the proof.
+    pub end: Option<f64>,
+    /// The time spent generating the traces of the shard in seconds.
+    pub trace_generation: Option<f64>,
+    /// The time spent committing to the traces of the shard in seconds.
+    pub commit: Option<f64>,
+    /// The time spent proving the shard in seconds.
+    pub proving: Option<f64>,
+}
+
+/// How a core proof split its work over the shards or segments.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Timeline {
+    /// The shards or segments by index.
+    pub shards: Vec<ShardTiming>,
+    /// Every span of the prover that closed while the proof was generated.
+    pub spans: Vec<SpanRecord>,
+}
+
+/// The timing of a span, kept in its extensions while it is open.
+struct SpanTiming {
+    start: Instant,
+    index: Option<u64>,
+    stage: Option<Stage>,
+}
+
+/// Finds the index field of a span.
+struct IndexVisitor(Option<u64>);
+
+impl Visit for IndexVisitor {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if INDEX_FIELDS.contains(&field.name()) {
+            self.0 = Some(value);
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if let Ok(value) = u64::try_from(value) {
+            self.record_u64(field, value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if let Ok(value) = format!("{:?}", value).parse::<u64>() {
+            self.record_u64(field, value);
+        }
+    }
+}
+
+/// Returns the stage a span is part of, judged by its name.
+fn stage(name: &str) -> Option<Stage> {
+    let name = name.to_lowercase();
+    if name.contains("commit") {
+        Some(Stage::Commit)
+    } else if name.contains("trace") {
+        Some(Stage::TraceGeneration)
+    } else if name.contains("prove") {
+        Some(Stage::Proving)
+    } else {
+        None
+    }
+}
+
+/// A layer that times the spans of the provers while a recording runs.
+struct SpanRecorder;
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for SpanRecorder {
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        if RECORDING.lock().unwrap().is_none() {
+            return;
+        }
+        let Some(span) = ctx.span(id) else { return };
+        let mut visitor = IndexVisitor(None);
+        attrs.record(&mut visitor);
+
+        // Inherit the index of the parents, and only count the outermost span of each stage in a
+        // shard. Spans of a shard under a span of the whole proof are still counted for the shard.
+        let parents: Vec<(Option<u64>, Option<Stage>)> = span
+            .scope()
+            .skip(1)
+            .filter_map(|parent| {
+                parent.extensions().get::<SpanTiming>().map(|t| (t.index, t.stage))
+            })
+            .collect();
+        let index = visitor.0.or_else(|| parents.iter().find_map(|(index, _)| *index));
+        let stage = stage(span.name()).filter(|stage| !parents.contains(&(index, Some(*stage))));
+
+        span.extensions_mut().insert(SpanTiming { start: Instant::now(), index, stage });
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let Some(timing) = span.extensions_mut().remove::<SpanTiming>() else { return };
+        if let Some(recording) = RECORDING.lock().unwrap().as_mut() {
+            recording.spans.push(SpanRecord {
+                name: span.name().to_string(),
+                index: timing.index,
+                stage: timing.stage,
+                start: timing.start.saturating_duration_since(recording.start).as_secs_f64(),
+                duration: timing.start.elapsed().as_secs_f64(),
+            });
+        }
+    }
+}
+
+/// Installs the logger of the binary, which also times the spans of the provers for the timeline.
+///
+/// Events are logged at the level of `RUST_LOG` (`info` by default). Spans are only timed while
+/// a timeline is recorded, so the provers don't open debug spans in runs that don't record one.
+pub fn init_logger() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let (level, handle) = reload::Layer::new(LevelFilter::OFF);
+    let installed = tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(filter))
+        .with(SpanRecorder.with_filter(level))
+        .try_init();
+    if installed.is_ok() {
+        let _ = RECORDER_LEVEL.set(Box::new(move |level| {
+            let _ = handle.reload(level);
+        }));
+    }
+}
+
+/// Runs the operation while recording the spans that close up to the debug level, timed from the
+/// start of the operation.
+pub fn record_spans<T, F: FnOnce() -> T>(operation: F) -> (T, Vec<SpanRecord>) {
+    let set_level = |level| RECORDER_LEVEL.get().into_iter().for_each(|set| set(level));
+    *RECORDING.lock().unwrap() = Some(Recording { start: Instant::now(), spans: Vec::new() });
+    set_level(LevelFilter::DEBUG);
+    let result = operation();
+    set_level(LevelFilter::OFF);
+    let recording = RECORDING.lock().unwrap().take();
+    (result, recording.map(|recording| recording.spans).unwrap_or_default())
+}
+
+impl Timeline {
+    /// Combines the shards reported by the prover with the stages of its spans.
+    ///
+    /// The spans of a shard add up to its stages and widen its start and end, while the values the
+    /// prover reported are kept. Shards that only the spans know about are added.
+    pub fn new(reported: Vec<ShardTiming>, spans: Vec<SpanRecord>) -> Self {
+        let mut shards: BTreeMap<u64, ShardTiming> =
+            reported.into_iter().map(|shard| (shard.index, shard)).collect();
+        let mut from_spans: BTreeMap<u64, ShardTiming> = BTreeMap::new();
+        for span in &spans {
+            let Some(index) = span.index else { continue };
+            let shard = from_spans
+                .entry(index)
+                .or_insert_with(|| ShardTiming { index, ..Default::default() });
+            let end = span.start + span.duration;
+            shard.start = Some(shard.start.map_or(span.start, |start| start.min(span.start)));
+            shard.end = Some(shard.end.map_or(end, |e| e.max(end)));
+            let stage = match span.stage {
+                Some(Stage::TraceGeneration) => &mut shard.trace_generation,
+                Some(Stage::Commit) => &mut shard.commit,
+                Some(Stage::Proving) => &mut shard.proving,
+                None => continue,
+            };
+            *stage.get_or_insert(0.0) += span.duration;
+        }
+
+        for (index, timing) in from_spans {
+            let shard =
+                shards.entry(index).or_insert_with(|| ShardTiming { index, ..Default::default() });
+            shard.start = shard.start.or(timing.start);
+            shard.end = shard.end.or(timing.end);
+            shard.trace_generation = shard.trace_generation.or(timing.trace_generation);
+            shard.commit = shard.commit.or(timing.commit);
+            shard.proving = shard.proving.or(timing.proving);
+        }
+        Self { shards: shards.into_values().collect(), spans }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serializes the tests that record, since the recording is global.
+    static RECORDING_TESTS: Mutex<()> = Mutex::new(());
+
+    fn span(
+        name: &str,
+        index: Option<u64>,
+        stage: Option<Stage>,
+        start: f64,
+        duration: f64,
+    ) -> SpanRecord {
+        SpanRecord { name: name.to_string(), index, stage, start, duration }
+    }
+
+    #[test]
+    fn spans_are_attributed_to_their_shards() {
+        let _serial = RECORDING_TESTS.lock().unwrap();
+        init_logger();
+        let (_, spans) = record_spans(|| {
+            let _prover = tracing::info_span!("prove shards").entered();
+            for shard in 0..2u64 {
+                let _shard = tracing::info_span!("prove shard", shard).entered();
+                tracing::debug_span!("generate traces").in_scope(|| {});
+                tracing::debug_span!("commit to traces").in_scope(|| {});
+                tracing::debug_span!("prove quotient").in_scope(|| {});
+            }
+        });
+        let (_, outside) = record_spans(|| {});
+
+        assert!(outside.is_empty());
+        let stages: Vec<_> = spans.iter().map(|s| (s.name.as_str(), s.index, s.stage)).collect();
+        assert_eq!(
+            stages[..4],
+            [
+                ("generate traces", Some(0), Some(Stage::TraceGeneration)),
+                ("commit to traces", Some(0), Some(Stage::Commit)),
+                ("prove quotient", Some(0), None),
+                ("prove shard", Some(0), Some(Stage::Proving)),
+            ]
+        );
+        assert_eq!(stages.last(), Some(&("prove shards", None, Some(Stage::Proving))));
+    }
+
+    #[test]
+    fn shards_are_inherited_by_spans_on_worker_threads() {
+        let _serial = RECORDING_TESTS.lock().unwrap();
+        init_logger();
+        let (_, spans) = record_spans(|| {
+            let _prover = tracing::info_span!("prove shards").entered();
+            // Like the workers of a thread pool, each thread enters the span of its shard, which
+            // was opened on the calling thread.
+            std::thread::scope(|scope| {
+                for shard in 0..2u64 {
+                    let span = tracing::info_span!("prove shard", shard);
+                    scope.spawn(move || {
+                        span.in_scope(|| {
+                            tracing::debug_span!("generate traces").in_scope(|| {});
+                            tracing::debug_span!("commit to traces").in_scope(|| {});
+                        })
+                    });
+                }
+                // A thread that didn't enter a shard doesn't know which one it works on.
+                scope.spawn(|| tracing::debug_span!("generate traces").in_scope(|| {}));
+            });
+        });
+
+        let timeline = Timeline::new(vec![], spans);
+        let shards: Vec<_> = timeline
+            .shards
+            .iter()
+            .map(|shard| (shard.index, shard.trace_generation.is_some(), shard.commit.is_some()))
+            .collect();
+        assert_eq!(shards, [(0, true, true), (1, true, true)]);
+        let unattributed = timeline.spans.iter().filter(|span| span.index.is_none()).count();
+        assert_eq!(unattributed, 2);
+    }
+
+    #[test]
+    fn reported_shards_are_combined_with_spans() {
+        let reported = vec![ShardTiming {
+            index: 0,
+            padded_cycles: Some(1 << 21),
+            proving: Some(5.0),
+            ..Default::default()
+        }];
+        let spans = vec![
+            span("generate traces", Some(0), Some(Stage::TraceGeneration), 1.0, 2.0),
+            span("generate traces", Some(1), Some(Stage::TraceGeneration), 1.5, 1.0),
+            span("prove shard", Some(1), Some(Stage::Proving), 3.0, 4.0),
+            span("prove", None, Some(Stage::Proving), 0.0, 10.0),
+        ];
+        let timeline = Timeline::new(reported, spans);
+
+        assert_eq!(timeline.shards.len(), 2);
+        let first = &timeline.shards[0];
+        assert_eq!(
+            (first.padded_cycles, first.proving, first.trace_generation),
+            (Some(1 << 21), Some(5.0), Some(2.0))
+        );
+        let second = &timeline.shards[1];
+        assert_eq!((second.start, second.end, second.proving), (Some(1.5), Some(7.0), Some(4.0)));
+        assert_eq!(timeline.spans.len(), 4);
+    }
+}
@@ -1,18 +1,5 @@
-/// An identifier used to select the program to evaluate.
-#[derive(clap::ValueEnum, Clone, PartialEq)]
-#[clap(rename_all = "kebab_case")]
-pub enum ProgramId {
-    Loop,
-    Fibonacci,
-    SSZWithdrawals,
-    Tendermint,
-    Sha2Chain,
-    Reth,
-    Fuel,
-}
-
 /// An identifier used to select the prover to evaluate.
-#[derive(clap::ValueEnum, Clone, PartialEq)]
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ProverId {
     Risc0,
     SP1,
@@ -24,22 +11,42 @@ pub enum HashFnId {
     Sha256,
     Poseidon,
     Blake3,
+    Blake2b,
     Keccak256,
 }
 
-impl ProgramId {
-    /// Convert the identifier to a string.
-    pub fn to_string(&self) -> String {
-        match self {
-            ProgramId::Loop => "loop".to_string(),
-            ProgramId::Fibonacci => "fibonacci".to_string(),
-            ProgramId::SSZWithdrawals => "ssz-withdrawals".to_string(),
-            ProgramId::Tendermint => "tendermint".to_string(),
-            ProgramId::Sha2Chain => "sha2-chain".to_string(),
-            ProgramId::Reth => "reth".to_string(),
-            ProgramId::Fuel => "fuel".to_string(),
-        }
-    }
+/// An identifier used to select how far through the proving pipeline to run.
+///
+/// Each mode runs the phases of the previous one and then some more:
+/// - `execute` only executes the program,
+/// - `core` also generates and verifies the core proof,
+/// - `compress` also compresses the core proof,
+/// - `full` also verifies the compressed proof.
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Execute,
+    Core,
+    Compress,
+    Full,
+}
+
+/// An identifier used to select whether the proofs are real.
+///
+/// - `real` generates and verifies real proofs,
+/// - `mock` executes the program for real, but stands in for the proofs with the mock prover of
+///   SP1 and the fake receipts of RISC0's dev mode, to check the pipeline in seconds.
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq)]
+pub enum ProverMode {
+    Real,
+    Mock,
+}
+
+/// An identifier used to select the format of the result files.
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Csv,
+    Jsonl,
+    Both,
 }
 
 impl ProverId {
@@ -59,7 +66,30 @@ impl HashFnId {
             HashFnId::Sha256 => "sha-256".to_string(),
             HashFnId::Poseidon => "poseidon".to_string(),
             HashFnId::Blake3 => "blake3".to_string(),
+            HashFnId::Blake2b => "blake2b".to_string(),
             HashFnId::Keccak256 => "keccak256".to_string(),
         }
     }
 }
+
+impl Mode {
+    /// Convert the identifier to a string.
+    pub fn to_string(&self) -> String {
+        match self {
+            Mode::Execute => "execute".to_string(),
+            Mode::Core => "core".to_string(),
+            Mode::Compress => "compress".to_string(),
+            Mode::Full => "full".to_string(),
+        }
+    }
+}
+
+impl ProverMode {
+    /// Convert the identifier to a string.
+    pub fn to_string(&self) -> String {
+        match self {
+            ProverMode::Real => "real".to_string(),
+            ProverMode::Mock => "mock".to_string(),
+        }
+    }
+}
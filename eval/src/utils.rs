@@ -1,65 +1,96 @@
 use core::time;
-use std::{env, fs, time::Instant};
+use std::{collections::BTreeMap, env, fs, time::Instant};
 
 use sp1_reth_primitives::SP1RethInput;
 
-use crate::{EvalArgs, ProgramId, ProverId};
+use crate::{
+    blocks::BLOCKS_DIR,
+    error::{EvalError, Result},
+    evaluator::GuestInput,
+    fixtures::get_fixture_input,
+    inputs::load_input,
+    programs::InputProvider,
+    EvalArgs,
+};
 
-pub fn get_elf(args: &EvalArgs) -> String {
-    let mut program_dir = args.program.to_string();
-    if args.program == ProgramId::Tendermint || args.program == ProgramId::Reth {
-        program_dir += "-";
-        program_dir += args.prover.to_string().as_str();
-    }
-
-    let current_dir = env::current_dir().expect("Failed to get current working directory");
-
-    let mut elf_path = current_dir.join(format!(
-        "programs/{}/target/riscv32im-succinct-zkvm-elf/release/{}",
-        program_dir, program_dir
-    ));
-
-    if args.prover == ProverId::Risc0 {
-        elf_path = current_dir.join(format!(
-            "programs/{}/target/riscv32im-risc0-zkvm-elf/release/{}",
-            program_dir, program_dir
-        ));
-    }
-
-    let elf_path_str = elf_path.to_str().expect("Failed to convert path to string").to_string();
-    println!("elf path: {}", elf_path_str);
-    elf_path_str
-}
-
-pub fn get_reth_input(args: &EvalArgs) -> SP1RethInput {
+pub fn get_reth_input(args: &EvalArgs) -> Result<SP1RethInput> {
     if let Some(block_number) = args.block_number {
-        let current_dir = env::current_dir().expect("Failed to get current working directory");
+        let current_dir = env::current_dir().map_err(|e| {
+            EvalError::Config(format!("Failed to get current working directory: {}", e))
+        })?;
 
-        let blocks_dir = current_dir.join("eval").join("blocks");
+        let blocks_dir = current_dir.join(BLOCKS_DIR);
 
         let file_path = blocks_dir.join(format!("{}.bin", block_number));
 
         if let Ok(bytes) = fs::read(file_path) {
-            bincode::deserialize(&bytes).expect("Unable to deserialize input")
+            bincode::deserialize(&bytes).map_err(|e| {
+                EvalError::Config(format!("Unable to deserialize block {}: {}", block_number, e))
+            })
         } else {
             let blocks: Vec<String> = fs::read_dir(&blocks_dir)
-                .unwrap_or_else(|_| panic!("Failed to read blocks directory: {:?}", blocks_dir))
+                .map_err(|_| {
+                    EvalError::Config(format!("Failed to read blocks directory: {:?}", blocks_dir))
+                })?
                 .filter_map(|entry| {
-                    entry.ok().and_then(|e| {
-                        e.path().file_stem().and_then(|n| n.to_str().map(String::from))
-                    })
+                    let path = entry.ok()?.path();
+                    if path.extension()? != "bin" {
+                        return None;
+                    }
+                    path.file_stem().and_then(|n| n.to_str().map(String::from))
                 })
                 .collect();
 
-            panic!(
+            Err(EvalError::Config(format!(
                 "Block {} not supported. Please choose from: {}",
                 block_number,
                 blocks.join(", ")
-            );
+            )))
         }
     } else {
-        panic!("Block number is required for Reth program");
+        Err(EvalError::Config("Block number is required for Reth program".to_string()))
+    }
+}
+
+/// Returns the inputs of the program from its provider, followed by the `--input` files.
+pub fn get_inputs(args: &EvalArgs, provider: InputProvider) -> Result<Vec<GuestInput>> {
+    let mut inputs = match provider {
+        InputProvider::None => vec![],
+        InputProvider::Block => vec![GuestInput::Reth(Box::new(get_reth_input(args)?))],
+        InputProvider::Sha2Chain(input) => vec![GuestInput::Sha2Chain(input)],
+        InputProvider::Fixture(set) => {
+            let fixture = args.fixture.as_deref().ok_or_else(|| {
+                EvalError::Config("A fixture is required for Fuel programs".to_string())
+            })?;
+            vec![GuestInput::Bytes(get_fixture_input(set, fixture)?)]
+        }
+    };
+    for path in &args.inputs {
+        inputs.push(load_input(path)?);
+    }
+    Ok(inputs)
+}
+
+/// Runs the operation with the environment variables set, restoring their previous values after.
+pub fn with_env<T, F: FnOnce() -> T>(vars: &BTreeMap<String, String>, operation: F) -> T {
+    let previous: Vec<_> = vars
+        .iter()
+        .map(|(key, value)| {
+            let previous = env::var(key).ok();
+            env::set_var(key, value);
+            (key, previous)
+        })
+        .collect();
+
+    let result = operation();
+
+    for (key, previous) in previous {
+        match previous {
+            Some(value) => env::set_var(key, value),
+            None => env::remove_var(key),
+        }
     }
+    result
 }
 
 pub fn time_operation<T, F: FnOnce() -> T>(operation: F) -> (T, time::Duration) {
@@ -0,0 +1,137 @@
+//! Runs every program of the registry through the whole pipeline with mock proofs, to catch
+//! breakage in the harness without waiting for real proofs.
+//!
+//! The runs use the ELFs of the last build, so the guests must be built beforehand with the build
+//! command and its toolchains. The test is ignored by default and run explicitly once they are.
+
+use std::{
+    fs,
+    os::unix::fs::symlink,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The root of the repository, which holds the program registry and the block inputs.
+fn repository() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..")
+}
+
+/// Returns the name of the first fixture of a set, as it is passed to `--fixture`.
+#[cfg(feature = "fuel")]
+fn first_fixture(set: &str) -> String {
+    use fuel_zkvm_primitives_test_fixtures::{
+        block_execution_fixtures::fixtures as block_execution,
+        decompression_fixtures as decompression,
+    };
+
+    let name = match set {
+        "block-execution" => serde_json::to_value(&block_execution::all_fixtures()[0]),
+        _ => serde_json::to_value(&decompression::all_fixtures()[0]),
+    };
+    name.unwrap().as_str().unwrap().to_string()
+}
+
+/// Returns the arguments that select the input of a program, or why it can't run in this build.
+fn input_args(input: Option<&toml::Value>) -> Result<Vec<String>, String> {
+    match input {
+        Some(toml::Value::String(provider)) if provider == "block" => {
+            let mut blocks: Vec<String> = fs::read_dir(repository().join("eval/blocks"))
+                .unwrap()
+                .filter_map(|entry| {
+                    let path = entry.unwrap().path();
+                    let stem = path.file_stem()?.to_str()?.to_string();
+                    (path.extension()? == "bin").then_some(stem)
+                })
+                .collect();
+            blocks.sort();
+            Ok(vec!["--block-number".to_string(), blocks[0].clone()])
+        }
+        #[cfg(feature = "fuel")]
+        Some(toml::Value::Table(provider)) if provider.contains_key("fixture") => {
+            let set = provider["fixture"].as_str().unwrap();
+            Ok(vec!["--fixture".to_string(), first_fixture(set)])
+        }
+        #[cfg(not(feature = "fuel"))]
+        Some(toml::Value::Table(provider)) if provider.contains_key("fixture") => {
+            Err("the Fuel fixtures require the fuel feature".to_string())
+        }
+        _ => Ok(vec![]),
+    }
+}
+
+#[test]
+#[ignore = "needs the guest ELFs, built beforehand with the build command"]
+fn every_program_runs_with_mock_proofs() {
+    let registry = repository().join("programs/programs.toml");
+    let programs: toml::Table = toml::from_str(&fs::read_to_string(registry).unwrap()).unwrap();
+
+    // Run in a directory of its own, so the results don't land among the real ones.
+    let dir = std::env::temp_dir().join(format!("eval-{}-mock", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    symlink(repository().join("programs"), dir.join("programs")).unwrap();
+    fs::create_dir_all(dir.join("eval")).unwrap();
+    symlink(repository().join("eval/blocks"), dir.join("eval/blocks")).unwrap();
+
+    let mut runs = Vec::new();
+    let mut failures = Vec::new();
+    for (program, spec) in &programs {
+        for prover in ["sp1", "risc0"] {
+            if !spec.as_table().unwrap().contains_key(prover) {
+                continue;
+            }
+            if prover == "risc0" && !cfg!(feature = "risc0") {
+                println!("skipping {} on risc0: the risc0 feature is disabled", program);
+                continue;
+            }
+            if spec[prover].get("zkvm_version").is_some() {
+                println!("skipping {} on {}: its guest is pinned to another zkVM", program, prover);
+                continue;
+            }
+            let inputs = match input_args(spec.get("input")) {
+                Ok(inputs) => inputs,
+                Err(reason) => {
+                    println!("skipping {} on {}: {}", program, prover, reason);
+                    continue;
+                }
+            };
+
+            let output = Command::new(env!("CARGO_BIN_EXE_sp1-benchmarks-eval"))
+                .current_dir(&dir)
+                .args(["run", "--program", program, "--prover", prover, "--hashfn", "poseidon"])
+                .args(["--shard-size", "21", "--filename", "mock", "--format", "jsonl"])
+                .args(["--prover-mode", "mock", "--skip-build"])
+                .args(inputs)
+                .output()
+                .unwrap();
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                failures.push(format!("{} on {}: {}", program, prover, stderr.trim()));
+            }
+            runs.push((program.clone(), prover));
+        }
+    }
+    assert!(failures.is_empty(), "failed runs:\n{}", failures.join("\n"));
+
+    // Every run appended a row that went through every phase with real cycles.
+    let rows: Vec<serde_json::Value> =
+        fs::read_to_string(dir.join("benchmarks/benchmarks_latest.jsonl"))
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+    assert_eq!(rows.len(), runs.len());
+    for (row, (program, prover)) in rows.iter().zip(&runs) {
+        assert_eq!(
+            (row["program"].as_str(), row["prover"].as_str()),
+            (Some(program.as_str()), Some(*prover))
+        );
+        assert_eq!(row["status"], "ok");
+        assert_eq!(row["prover_mode"], "mock");
+        let cycles = row["cycles"].as_u64().unwrap();
+        assert!(cycles > 0, "{} on {} reported no cycles", program, prover);
+        // Mock proofs aren't timed, so their phases are recorded as taking no time.
+        assert_eq!(row["compress_verify_duration_median"].as_f64(), Some(0.0));
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
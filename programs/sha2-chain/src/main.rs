@@ -0,0 +1,62 @@
+#![no_main]
+
+use serde::Deserialize;
+use sha2::Digest;
+
+#[cfg(feature = "risc0")]
+risc0_zkvm::guest::entry!(main);
+
+#[cfg(feature = "sp1")]
+sp1_zkvm::entrypoint!(main);
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "sp1")] {
+        use sha2_sp1::Sha256 as PatchedSha256;
+
+        fn read<T: serde::de::DeserializeOwned>() -> T {
+            sp1_zkvm::io::read()
+        }
+    } else if #[cfg(feature = "risc0")] {
+        use sha2_risc0::Sha256 as PatchedSha256;
+
+        fn read<T: serde::de::DeserializeOwned>() -> T {
+            risc0_zkvm::guest::env::read()
+        }
+    }
+}
+
+/// The parameters of the chain, written to stdin by the eval binary.
+#[derive(Deserialize)]
+struct Input {
+    /// The number of hashes in the chain.
+    iterations: u32,
+    /// The size in bytes of each preimage.
+    preimage_size: u32,
+    /// Whether to hash with the SHA-256 precompile of the prover or in software.
+    precompile: bool,
+}
+
+/// Hashes a chain of preimages of `preimage_size` bytes, each starting with the digest of the
+/// previous one, and returns the last digest.
+fn hash_chain<D: Digest>(iterations: u32, preimage_size: usize) -> Vec<u8> {
+    let mut preimage = vec![0u8; preimage_size];
+    let mut digest = Vec::new();
+    for _ in 0..iterations {
+        let n = digest.len().min(preimage_size);
+        preimage[..n].copy_from_slice(&digest[..n]);
+        digest = D::digest(&preimage).to_vec();
+    }
+    digest
+}
+
+pub fn main() {
+    let input: Input = read();
+    let preimage_size = input.preimage_size as usize;
+    let digest = if input.precompile {
+        hash_chain::<PatchedSha256>(input.iterations, preimage_size)
+    } else {
+        hash_chain::<sha2::Sha256>(input.iterations, preimage_size)
+    };
+    println!("bytes hashed: {}", input.iterations as u64 * preimage_size as u64);
+    println!("digest: {:02x?}", digest);
+}